@@ -22,6 +22,8 @@ type TriggerColls = TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>;
 #[allow(dead_code)]
 type TriggerCollRec = TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>;
 type PhysicsSettings = PhysicsSettingsGeneric<TriggerRxKind, TriggerTxKind>;
+#[allow(dead_code)]
+type PhysicsQuery<'w, 's> = PhysicsQueryGeneric<'w, 's, TriggerTxKind>;
 
 #[derive(Clone, Copy, Debug, Default, EnumIter, Reflect, PartialEq, Eq, std::hash::Hash)]
 enum LdtkRoot {
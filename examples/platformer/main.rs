@@ -28,6 +28,8 @@ type TriggerColls = TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>;
 #[expect(dead_code)]
 type TriggerCollRec = TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>;
 type PhysicsSettings = PhysicsSettingsGeneric<TriggerRxKind, TriggerTxKind>;
+#[expect(dead_code)]
+type PhysicsQuery<'w, 's> = PhysicsQueryGeneric<'w, 's, TriggerTxKind>;
 
 fn main() {
     let mut app = App::new();
@@ -43,7 +45,7 @@ fn main() {
             ..default()
         },
         ldtk_settings: ldtk::LdtkSettings::default(),
-        physics_settings: PhysicsSettings::default(),
+        physics_settings: PhysicsSettings::default().with_gravity(300),
         deterministic: false,
     });
     app.add_plugins(EguiPlugin::default()).add_plugins(
@@ -84,25 +84,23 @@ fn update_falling_platform_spawners(
 fn update_falling_platforms(
     mut spawners: Query<&mut FallingPlatformSpawner>,
     waiting_to_fall: Query<(Entity, &FallingPlatform, &StaticTx), Without<Dyno>>,
-    mut falling: Query<&mut Dyno, With<FallingPlatform>>,
     static_colls: Res<StaticColls>,
     mut commands: Commands,
-    bullet_time: Res<BulletTime>,
 ) {
     for (eid, falling_platform, stx) in &waiting_to_fall {
         if static_colls
             .iter_refs(&stx.coll_keys)
-            .any(|coll| coll.rx_kind == StaticRxKind::Default)
+            .any(|coll| coll.rx_kind == StaticRxKind::Default && !coll.pass_ignored)
         {
-            commands.entity(eid).insert(Dyno::default());
+            commands.entity(eid).insert(
+                DynoBody::default()
+                    .with_gravity_scale(0.5)
+                    .with_max_speed(Fx::MAX, 75),
+            );
             let mut spawner = spawners.get_mut(falling_platform.spawned_by).unwrap();
             spawner.time_till_spawn = Some(fx!(1));
         }
     }
-    for mut dyno in &mut falling {
-        dyno.vel.y -= bullet_time.delta_secs() * fx!(150);
-        dyno.vel.y = dyno.vel.y.max(fx!(-75));
-    }
 }
 
 pub(super) fn register_platforms(app: &mut App) {
@@ -2,7 +2,6 @@ use bevy::{color::palettes::tailwind, prelude::*};
 use bevy_2delight::prelude::*;
 
 use crate::{
-    input::Combos,
     ldtk::{LdtkBundleEntityPlugin, LdtkRoot, LdtkRootRes},
     TriggerColls, TriggerRx, TriggerRxKind, TriggerTxKind,
 };
@@ -40,11 +39,7 @@ fn update_player_input(mut player_input: ResMut<PlayerInput>, keyboard: Res<Butt
 }
 
 #[derive(Component)]
-pub struct Player {
-    /// How much time the player has left to input a jump and get a jump
-    /// Kinda like a can_jump boolean but w/ coyote frames
-    jump_time: Fx,
-}
+pub struct Player;
 #[derive(Bundle)]
 struct PlayerBundle {
     name: Name,
@@ -54,6 +49,8 @@ struct PlayerBundle {
     flicker: LightFlicker,
     pos: Pos,
     dyno: Dyno,
+    body: DynoBody,
+    controller: PlatformerController,
     static_rx: StaticRx,
     trigger_rx: TriggerRx,
 }
@@ -61,14 +58,18 @@ impl PlayerBundle {
     fn new(pos: Pos) -> Self {
         Self {
             name: Name::new("Player"),
-            player: Player {
-                jump_time: Fx::ZERO,
-            },
+            player: Player,
             anim: AnimMan::new(PlayerAnim::Idle),
             light: CircleLight::strength(64.0),
             flicker: LightFlicker::new(64.0, 2.0, 2.0, 1.0, 0.15, 0.05),
             pos: pos.with_z(10),
             dyno: Dyno::default(),
+            body: DynoBody::default().with_max_speed(100, 100),
+            controller: PlatformerController::default()
+                .with_run(100, 600, 300)
+                .with_jump(100, 0.5)
+                .with_wall(20, FVec2::new(80, 100))
+                .with_bindings(Stick::Left, Butt::West),
             static_rx: StaticRx::single(StaticRxKind::Default, HBox::new(7, 12).with_offset(0, -1)),
             trigger_rx: TriggerRx::single(
                 TriggerRxKind::Player,
@@ -79,28 +80,14 @@ impl PlayerBundle {
 }
 /// Logic that we always perform for the player, regardless of state
 fn update_player_always(
-    mut player_q: Query<(Entity, &mut Dyno, &mut Player, &StaticRx, &TriggerRx)>,
-    bullet_time: Res<BulletTime>,
-    scolls: Res<StaticColls>,
+    player_q: Query<(Entity, &TriggerRx), With<Player>>,
     tcolls: Res<TriggerColls>,
     mut commands: Commands,
     keyboard: Res<ButtonInput<KeyCode>>,
 ) {
-    let Ok((eid, mut dyno, mut player, srx, trx)) = player_q.single_mut() else {
+    let Ok((eid, trx)) = player_q.single() else {
         return;
     };
-    // Gravity
-    dyno.vel.y -= bullet_time.delta_secs() * fx!(300);
-    // Jump timing
-    if scolls.iter_refs(&srx.coll_keys).any(|coll| {
-        coll.push.y > Fx::ZERO && matches!(coll.tx_kind, StaticTxKind::Solid | StaticTxKind::PassUp)
-    }) {
-        player.jump_time = fx!(0.1);
-    } else {
-        if player.jump_time > Fx::ZERO {
-            player.jump_time -= bullet_time.real_delta_secs();
-        }
-    }
     // Die from spikes
     if tcolls
         .get_refs(&trx.coll_keys)
@@ -113,87 +100,33 @@ fn update_player_always(
         commands.entity(eid).despawn();
     }
 }
-#[derive(Resource, Reflect)]
-struct MovementConsts {
-    air_speed: i32,
-    air_drag: f32,
-    ground_speed: i32,
-    ground_drag: f32,
-    jump_speed: i32,
-    max_component_speed: i32,
-}
-impl Default for MovementConsts {
-    fn default() -> Self {
-        Self {
-            air_speed: 300,
-            air_drag: 0.5,
-            ground_speed: 300,
-            ground_drag: 0.1,
-            jump_speed: 100,
-            max_component_speed: 100,
-        }
-    }
-}
-/// Logic for updating the player, matched on state
+/// Logic for updating the player, matched on state.
+/// NOTE: The actual moving/jumping is done by the `PlatformerController`, this is just anims and extras.
 fn update_player_stateful(
-    mut player_q: Query<(&mut AnimMan<PlayerAnim>, &mut Dyno, &mut Player, &StaticRx)>,
-    scolls: Res<StaticColls>,
-    input: Res<Input>,
+    mut player_q: Query<(
+        &mut AnimMan<PlayerAnim>,
+        &Dyno,
+        &PlatformerController,
+        &mut StaticRx,
+        &Contacts,
+    )>,
     old_input: Res<PlayerInput>,
-    bullet_time: Res<BulletTime>,
-    consts: Res<MovementConsts>,
 ) {
-    let Ok((mut anim, mut dyno, mut player, srx)) = player_q.single_mut() else {
+    let Ok((mut anim, dyno, ctrl, mut srx, contacts)) = player_q.single_mut() else {
         return;
     };
-    let on_ground = scolls.iter_refs(&srx.coll_keys).any(|coll| {
-        coll.push.y > Fx::ZERO && matches!(coll.tx_kind, StaticTxKind::Solid | StaticTxKind::PassUp)
-    });
+    let on_ground = ctrl.is_grounded();
 
-    // Horizontal movement
-    match anim.get_state() {
-        PlayerAnim::Air | PlayerAnim::Jump => {
-            if old_input.dir.x < Fx::ZERO {
-                if dyno.vel.x > Fx::ZERO {
-                    dyno.vel.x *= fx!(consts.air_drag);
-                }
-                dyno.vel.x -= fx!(consts.air_speed) * bullet_time.delta_secs();
-            } else if old_input.dir.x > Fx::ZERO {
-                if dyno.vel.x < Fx::ZERO {
-                    dyno.vel.x *= fx!(consts.air_drag);
-                }
-                dyno.vel.x += fx!(consts.air_speed) * bullet_time.delta_secs();
-            } else {
-                dyno.vel.x *= fx!(consts.air_drag);
-            }
-        }
-        PlayerAnim::Idle | PlayerAnim::Run | PlayerAnim::Land => {
-            if old_input.dir.x < Fx::ZERO {
-                if dyno.vel.x > Fx::ZERO {
-                    dyno.vel.x *= fx!(consts.ground_drag);
-                }
-                dyno.vel.x -= fx!(consts.ground_speed) * bullet_time.delta_secs();
-            } else if old_input.dir.x > Fx::ZERO {
-                if dyno.vel.x < Fx::ZERO {
-                    dyno.vel.x *= fx!(consts.ground_drag);
-                }
-                dyno.vel.x += fx!(consts.ground_speed) * bullet_time.delta_secs();
-            } else {
-                dyno.vel.x *= fx!(consts.ground_drag);
-            }
-        }
-    }
     // Jumping
-    if player.jump_time > Fx::ZERO
-        && input.combos.pressed(Combos::Jump as u32)
-        && matches!(
-            anim.get_state(),
-            PlayerAnim::Idle | PlayerAnim::Run | PlayerAnim::Land
-        )
-    {
+    if ctrl.just_jumped() {
         anim.set_state(PlayerAnim::Jump);
-        dyno.vel.y = fx!(consts.jump_speed);
-        player.jump_time = Fx::ZERO;
+    }
+    // Dropping through platforms
+    let on_pass_up = contacts
+        .ground()
+        .is_some_and(|ground| ground.tx_kind == StaticTxKind::PassUp);
+    if on_pass_up && old_input.dir.y < Fx::ZERO {
+        srx.drop_through(4);
     }
     // State transitions
     match anim.get_state() {
@@ -230,13 +163,6 @@ fn update_player_stateful(
             // Do nothing. We always finish the land anim unless pulled out to jump above.
         }
     }
-    // Limit component speed (TODO: Fixed point square root...)
-    if dyno.vel.x.abs() > fx!(consts.max_component_speed) {
-        dyno.vel.x = dyno.vel.x.signum() * fx!(consts.max_component_speed);
-    }
-    if dyno.vel.y.abs() > fx!(consts.max_component_speed) {
-        dyno.vel.y = dyno.vel.y.signum() * fx!(consts.max_component_speed);
-    }
 }
 
 #[derive(Component)]
@@ -365,8 +291,6 @@ pub(super) fn register_player(app: &mut App) {
     ));
 
     app.insert_resource(PlayerInput::default());
-    app.insert_resource(MovementConsts::default());
-    debug_resource!(app, MovementConsts);
 
     app.add_systems(
         Update,
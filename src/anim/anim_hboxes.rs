@@ -0,0 +1,84 @@
+//! Hitboxes authored as slices in Aseprite. Each named slice becomes an hbox with a marker,
+//! re-read every time the anim changes frame. The physics side then copies them into any
+//! `StaticRx`/`StaticTx`/`TriggerRx`/`TriggerTx` comps that have the same marker.
+
+use bevy::prelude::*;
+
+use crate::{fx, prelude::*};
+
+use super::{anim_man::AnimMan, anim_res::AnimRes, anim_traits::AnimStateMachine};
+
+/// Goes next to an `AnimMan`. Maps slice names to the hbox markers they should drive.
+/// Slices that are hidden (or missing) on the current frame switch off the comps they drive until they show up again.
+#[derive(Component, Clone, Debug, Default)]
+pub struct AnimHBoxes {
+    markers: Vec<(String, HBoxMarker)>,
+    /// `None` when the slice is hidden on the current frame
+    current: Vec<(HBoxMarker, Option<HBox>)>,
+}
+impl AnimHBoxes {
+    pub fn with_slice(mut self, name: &str, marker: HBoxMarker) -> Self {
+        self.markers.push((name.to_string(), marker));
+        self
+    }
+    /// The hboxes showing on the current frame, relative to the entity (like any other hbox)
+    pub fn get_hboxes(&self) -> impl Iterator<Item = &HBox> {
+        self.current.iter().filter_map(|(_, hbox)| hbox.as_ref())
+    }
+    pub fn get_hbox(&self, marker: HBoxMarker) -> Option<&HBox> {
+        self.current
+            .iter()
+            .find(|(other, _)| *other == marker)?
+            .1
+            .as_ref()
+    }
+    /// If this marker is driven by a slice that's hidden on the current frame
+    pub fn is_hidden(&self, marker: HBoxMarker) -> bool {
+        self.current
+            .iter()
+            .any(|(other, hbox)| *other == marker && hbox.is_none())
+    }
+}
+
+pub(super) fn update_anim_hboxes<StateMachine: AnimStateMachine>(
+    mut anims: Query<(Ref<AnimMan<StateMachine>>, &mut AnimHBoxes)>,
+    anim_res: Res<AnimRes<StateMachine>>,
+) {
+    let size = anim_res.get_size().as_ivec2();
+    for (anim_man, mut anim_hboxes) in &mut anims {
+        let frame_change = anim_man.delta_ix().is_some()
+            || anim_man.delta_flip_x().is_some()
+            || anim_man.delta_flip_y().is_some();
+        if !frame_change && !anim_man.is_added() && !anim_hboxes.is_added() {
+            continue;
+        }
+        // Sprites are centered on the entity (plus the state's offset), rounded down for odd sizes
+        let offset = anim_man.get_state().get_offset();
+        let bottom_left = FVec2::from(offset - IVec2::new(size.x / 2, size.y / 2));
+        let center = bottom_left + FVec2::from(size) / fx!(2);
+        let current = anim_hboxes
+            .markers
+            .iter()
+            .map(|(name, marker)| {
+                let Some(rect) = anim_res.get_slice(anim_man.get_state(), name, anim_man.get_ix())
+                else {
+                    return (*marker, None);
+                };
+                // Aseprite's y goes down from the top of the frame
+                let mut x = bottom_left.x + fx!(rect.min.x + rect.max.x) / 2;
+                let mut y = bottom_left.y + fx!(size.y) - fx!(rect.min.y + rect.max.y) / 2;
+                if anim_man.get_flip_x() {
+                    x = center.x * 2 - x;
+                }
+                if anim_man.get_flip_y() {
+                    y = center.y * 2 - y;
+                }
+                let hbox = HBox::new(rect.width(), rect.height())
+                    .with_offset(x, y)
+                    .with_marker(*marker);
+                (*marker, Some(hbox))
+            })
+            .collect();
+        anim_hboxes.current = current;
+    }
+}
@@ -4,6 +4,7 @@ use bevy::prelude::*;
 use crate::fx;
 use crate::prelude::{BulletTime, Fx, Layer};
 
+use super::anim_hboxes::update_anim_hboxes;
 use super::anim_man::{AnimMan, AnimNextState, AnimObserveStateChanges};
 use super::anim_plugin::AnimDefaults;
 use super::anim_res::AnimRes;
@@ -287,6 +288,7 @@ pub(crate) fn register_anim_logic<StateMachine: AnimStateMachine>(app: &mut App)
             update_anim_time,
             progress_animations::<StateMachine>,
             bless_animations::<StateMachine>,
+            update_anim_hboxes::<StateMachine>,
         )
             .chain()
             .in_set(AnimPreSet),
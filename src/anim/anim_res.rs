@@ -3,11 +3,21 @@ use serde_json::Value;
 
 use crate::prelude::*;
 
+/// One key of an Aseprite slice. Holds from `frame` until the next key.
+#[derive(Clone, Debug)]
+pub struct SliceKey {
+    pub frame: u32,
+    /// In image pixels, from the top left of the frame. `None` means the slice is hidden.
+    pub bounds: Option<URect>,
+}
+
 #[derive(Clone, Debug)]
 pub struct TagInfo {
     pub w: u32,
     pub h: u32,
     pub length: u32,
+    /// Slice name -> keys, sorted by frame
+    pub slices: HashMap<String, Vec<SliceKey>>,
 }
 impl TagInfo {
     pub fn from_path(
@@ -45,18 +55,83 @@ impl TagInfo {
             .and_then(|h| h.as_u64())
             .ok_or("Invalid height")? as u32;
 
+        // Slice keys use frame numbers from the whole .aseprite file, so find where our tag starts.
+        // Each tag is exported to its own json, named after the tag.
+        let meta = json.get("meta");
+        let tag_name = path.file_stem().and_then(|stem| stem.to_str());
+        let frame_offset = meta
+            .and_then(|m| m.get("frameTags"))
+            .and_then(|t| t.as_array())
+            .and_then(|tags| {
+                tags.iter().find_map(|tag| {
+                    let name = tag.get("name")?.as_str()?;
+                    (Some(name) == tag_name).then_some(tag.get("from")?.as_u64()? as u32)
+                })
+            })
+            .unwrap_or(0);
+        let mut slices = HashMap::<String, Vec<SliceKey>>::default();
+        for slice in meta
+            .and_then(|m| m.get("slices"))
+            .and_then(|s| s.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let name = slice
+                .get("name")
+                .and_then(|n| n.as_str())
+                .ok_or("Slice without a name")?;
+            let mut keys = vec![];
+            for key in slice
+                .get("keys")
+                .and_then(|k| k.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let frame = key
+                    .get("frame")
+                    .and_then(|f| f.as_u64())
+                    .ok_or("Slice key without a frame")? as u32;
+                let bounds = key.get("bounds").ok_or("Slice key without bounds")?;
+                let get =
+                    |field: &str| -> Option<u32> { Some(bounds.get(field)?.as_u64()? as u32) };
+                let (Some(x), Some(y), Some(w), Some(h)) = (get("x"), get("y"), get("w"), get("h"))
+                else {
+                    return Err("Invalid slice bounds".into());
+                };
+                keys.push(SliceKey {
+                    frame: frame.saturating_sub(frame_offset),
+                    bounds: (w > 0 && h > 0).then(|| URect::new(x, y, x + w, y + h)),
+                });
+            }
+            keys.sort_by_key(|key| key.frame);
+            slices.insert(name.to_string(), keys);
+        }
+
         Ok(TagInfo {
             w: width,
             h: height,
             length: frame_count,
+            slices,
         })
     }
+
+    /// Where the given slice is on the given frame, if it's there at all
+    pub fn get_slice(&self, name: &str, ix: u32) -> Option<URect> {
+        self.slices
+            .get(name)?
+            .iter()
+            .take_while(|key| key.frame <= ix)
+            .last()?
+            .bounds
+    }
 }
 
 #[derive(Resource, Reflect, Debug)]
 pub(super) struct AnimRes<StateMachine: AnimStateMachine> {
     size: UVec2,
     lengths: HashMap<StateMachine, u32>,
+    #[reflect(ignore)]
+    tag_infos: HashMap<StateMachine, TagInfo>,
     has_brightness: bool,
     has_reflexivity: bool,
 }
@@ -76,9 +151,10 @@ impl<StateMachine: AnimStateMachine> AnimRes<StateMachine> {
         Self {
             size,
             lengths: pixel_tag_infos
-                .into_iter()
-                .map(|(state, t)| (state, t.length))
+                .iter()
+                .map(|(state, t)| (*state, t.length))
                 .collect(),
+            tag_infos: pixel_tag_infos.into_iter().collect(),
             has_brightness: brightness_path.exists(),
             has_reflexivity: reflexivity_path.exists(),
         }
@@ -90,6 +166,9 @@ impl<StateMachine: AnimStateMachine> AnimRes<StateMachine> {
     pub fn get_length(&self, state: StateMachine) -> u32 {
         *self.lengths.get(&state).unwrap_or(&1)
     }
+    pub fn get_slice(&self, state: StateMachine, name: &str, ix: u32) -> Option<URect> {
+        self.tag_infos.get(&state)?.get_slice(name, ix)
+    }
     pub fn has_brightness(&self) -> bool {
         self.has_brightness
     }
@@ -113,3 +192,42 @@ pub(super) fn register_anim_res<StateMachine: AnimStateMachine>(app: &mut App) {
         app.add_observer(on_reload_anims::<StateMachine>);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_frames_count_from_the_tag_named_like_the_file() {
+        // Two tags of the same length, so only the name can tell them apart
+        let json = r#"{
+            "frames": {
+                "lenny #run 0.aseprite": { "sourceSize": { "w": 16, "h": 16 } },
+                "lenny #run 1.aseprite": { "sourceSize": { "w": 16, "h": 16 } }
+            },
+            "meta": {
+                "frameTags": [
+                    { "name": "idle", "from": 0, "to": 1 },
+                    { "name": "run", "from": 2, "to": 3 }
+                ],
+                "slices": [{
+                    "name": "hurt",
+                    "keys": [
+                        { "frame": 2, "bounds": { "x": 1, "y": 2, "w": 3, "h": 4 } },
+                        { "frame": 3, "bounds": { "x": 0, "y": 0, "w": 0, "h": 0 } }
+                    ]
+                }]
+            }
+        }"#;
+        let dir = std::env::temp_dir().join(format!("anim_res_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("run.json");
+        std::fs::write(&path, json).unwrap();
+        let info = TagInfo::from_path(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(info.length, 2);
+        assert_eq!(info.get_slice("hurt", 0), Some(URect::new(1, 2, 4, 6)));
+        assert_eq!(info.get_slice("hurt", 1), None);
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 mod anim_collect;
+mod anim_hboxes;
 mod anim_logic;
 mod anim_man;
 mod anim_plugin;
@@ -17,6 +18,7 @@ pub(crate) struct AnimPostSet;
 pub mod prelude {
     pub use super::{
         anim_collect::_AnimWizardry,
+        anim_hboxes::AnimHBoxes,
         anim_man::{AnimDelta, AnimMan, AnimNextState, AnimObserveStateChanges},
         anim_plugin::*,
         anim_time::{AnimTime, AnimTimeClass},
@@ -101,7 +101,8 @@ pub(super) fn remove_light_source(mut world: bevy::ecs::world::DeferredWorld, ho
 /// Marks a component as occluding 2d light.
 /// The internal spatial hashes this depends on will only recalculate when one
 /// of Pos or OccludeLight on the entity has changed.
-/// So, if you to change underlying static HBoxes, be warned.
+/// So, if you change underlying static HBoxes, do it with `StaticTxHBoxes`,
+/// which takes care of that for you.
 #[derive(Component, Clone)]
 #[component(on_add = on_add_occlude_light)]
 #[component(on_remove = on_remove_spat_hash::<SpatHashOccludeLight>)]
@@ -21,13 +21,13 @@ impl Ord for Key {
     }
 }
 impl Key {
-    fn left(&self) -> Self {
+    pub(crate) fn left(&self) -> Self {
         Self {
             x: self.x - 1,
             y: self.y,
         }
     }
-    fn right(&self) -> Self {
+    pub(crate) fn right(&self) -> Self {
         Self {
             x: self.x + 1,
             y: self.y,
@@ -39,7 +39,7 @@ impl Key {
             y: self.y,
         }
     }
-    fn up(&self) -> Self {
+    pub(crate) fn up(&self) -> Self {
         Self {
             x: self.x,
             y: self.y + 1,
@@ -51,13 +51,20 @@ impl Key {
             y: self.y + amt,
         }
     }
-    fn down(&self) -> Self {
+    pub(crate) fn down(&self) -> Self {
         Self {
             x: self.x,
             y: self.y - 1,
         }
     }
 
+    pub(crate) fn from_cell(cell: IVec2) -> Self {
+        Key {
+            x: cell.x,
+            y: cell.y,
+        }
+    }
+
     pub(crate) fn from_pos(pos: Pos, grid_size: u32) -> Self {
         let iv = IVec2::new(pos.x.round().to_num(), pos.y.round().to_num());
         debug_assert!(iv.x % grid_size as i32 == 0);
@@ -90,11 +97,8 @@ impl<T> Ord for Pixel<T> {
     }
 }
 impl<T> Pixel<T> {
-    pub(crate) fn from_pos(pos: Pos, grid_size: u32, payload: T) -> Self {
-        Self {
-            key: Key::from_pos(pos, grid_size),
-            payload,
-        }
+    pub(crate) fn new(key: Key, payload: T) -> Self {
+        Self { key, payload }
     }
 }
 
@@ -8,14 +8,16 @@ use crate::fx;
 use super::{Deterministic, Fx};
 
 const FRAMERATE: u32 = 60;
+/// When physics has a fixed timestep we don't lock the framerate, but still don't want huge jumps
+const MIN_UNLOCKED_FRAMERATE: u32 = 20;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct BulletTimeEffect {
     factor: Fx,
     time_left: Fx,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct BulletTimeState {
     base: Fx,
     effects: Vec<BulletTimeEffect>,
@@ -49,11 +51,12 @@ impl BulletTimeState {
 }
 
 /// How much in-game time has happened. Basically time but accounts for slowdown.
-#[derive(Resource, Debug, Default)]
+#[derive(Resource, Clone, Debug, Default)]
 pub struct BulletTime {
     state: BulletTimeState,
     duration: Fx,
     real_duration: Fx,
+    unlocked: bool,
 }
 impl BulletTime {
     pub fn delta_secs(&self) -> Fx {
@@ -62,6 +65,10 @@ impl BulletTime {
     pub fn real_delta_secs(&self) -> Fx {
         self.real_duration
     }
+    /// The current time factor, accounting for any active effects
+    pub fn get_factor(&self) -> Fx {
+        self.state.to_factor()
+    }
     pub fn get_base(&self) -> Fx {
         self.state.base
     }
@@ -77,6 +84,15 @@ impl BulletTime {
     pub fn clear_effects(&mut self) {
         self.state.effects.clear();
     }
+    /// Always steps by `delta`, without needing `Time` or the plugin. Only for tests.
+    #[cfg(test)]
+    pub(crate) fn with_delta_secs(delta: Fx) -> Self {
+        Self {
+            duration: delta,
+            real_duration: delta,
+            ..default()
+        }
+    }
 }
 
 fn update_bullet_time(
@@ -86,6 +102,8 @@ fn update_bullet_time(
 ) {
     let time_fx = if deterministic.0 {
         fx!(1) / fx!(FRAMERATE)
+    } else if bullet_time.unlocked {
+        fx!(time.delta_secs()).min(fx!(1) / fx!(MIN_UNLOCKED_FRAMERATE))
     } else {
         let min_fps = fx!(1) / fx!(FRAMERATE - 4);
         let max_fps = fx!(1) / fx!(FRAMERATE + 4);
@@ -96,17 +114,31 @@ fn update_bullet_time(
     bullet_time.real_duration = time_fx;
 }
 
+/// By default we lock to `FRAMERATE` and physics steps once per frame.
+/// If physics has its own fixed timestep, we let the framerate match the display instead.
 #[derive(Default)]
-pub(crate) struct BulletTimePlugin;
+pub(crate) struct BulletTimePlugin {
+    unlocked: bool,
+}
+impl BulletTimePlugin {
+    pub(crate) fn new(unlocked: bool) -> Self {
+        Self { unlocked }
+    }
+}
 impl Plugin for BulletTimePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(BulletTime::default());
+        app.insert_resource(BulletTime {
+            unlocked: self.unlocked,
+            ..default()
+        });
         app.add_systems(First, update_bullet_time);
         app.add_plugins(bevy_framepace::FramepacePlugin);
         app.insert_resource(bevy_framepace::FramepaceSettings {
-            limiter: bevy_framepace::Limiter::Manual(Duration::from_secs_f64(
-                1.0 / FRAMERATE as f64,
-            )),
+            limiter: if self.unlocked {
+                bevy_framepace::Limiter::Auto
+            } else {
+                bevy_framepace::Limiter::Manual(Duration::from_secs_f64(1.0 / FRAMERATE as f64))
+            },
         });
     }
 }
@@ -0,0 +1,201 @@
+//! Deterministic trig for `Fx`. Everything is shifts and adds (CORDIC), so it comes out
+//! bit-for-bit the same on every machine, unlike `f32::sin` and friends.
+//! Angles are in radians. Accurate to within 1e-8, which is way more than pixels need.
+
+use super::Fx;
+
+pub const PI: Fx = Fx::PI;
+pub const TAU: Fx = Fx::TAU;
+pub const FRAC_PI_2: Fx = Fx::FRAC_PI_2;
+
+const ITERATIONS: usize = 32;
+
+/// atan(2^-i) for each iteration
+const ATANS: [Fx; ITERATIONS] = {
+    const BITS: [i64; ITERATIONS] = [
+        3373259426, 1991351318, 1052175346, 534100635, 268086748, 134174063, 67103403, 33553749,
+        16777131, 8388597, 4194303, 2097152, 1048576, 524288, 262144, 131072, 65536, 32768, 16384,
+        8192, 4096, 2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4, 2,
+    ];
+    let mut result = [Fx::ZERO; ITERATIONS];
+    let mut ix = 0;
+    while ix < ITERATIONS {
+        result[ix] = Fx::from_bits(BITS[ix]);
+        ix += 1;
+    }
+    result
+};
+
+/// How much CORDIC stretches vectors by, inverted. Starting with this cancels it out.
+const INV_GAIN: Fx = Fx::from_bits(2608131496);
+
+/// Wraps an angle into (-PI, PI]
+pub fn wrap_angle(angle: Fx) -> Fx {
+    let wrapped = angle.rem_euclid(TAU);
+    if wrapped > PI {
+        wrapped - TAU
+    } else {
+        wrapped
+    }
+}
+
+/// Both at once, since that's what CORDIC gives anyway
+pub fn sin_cos(angle: Fx) -> (Fx, Fx) {
+    // CORDIC only converges within about ±99°, so fold the back half onto the front
+    let mut z = wrap_angle(angle);
+    let mut flip = false;
+    if z > FRAC_PI_2 {
+        z -= PI;
+        flip = true;
+    } else if z < -FRAC_PI_2 {
+        z += PI;
+        flip = true;
+    }
+    let (mut x, mut y) = (INV_GAIN, Fx::ZERO);
+    for (ix, atan) in ATANS.iter().enumerate() {
+        let (dx, dy) = (y >> ix as u32, x >> ix as u32);
+        if z >= Fx::ZERO {
+            (x, y, z) = (x - dx, y + dy, z - *atan);
+        } else {
+            (x, y, z) = (x + dx, y - dy, z + *atan);
+        }
+    }
+    if flip {
+        (-y, -x)
+    } else {
+        (y, x)
+    }
+}
+
+pub fn sin(angle: Fx) -> Fx {
+    sin_cos(angle).0
+}
+
+pub fn cos(angle: Fx) -> Fx {
+    sin_cos(angle).1
+}
+
+/// Angle of the point (x, y) from the positive x axis, in (-PI, PI]. Zero for the origin.
+pub fn atan2(y: Fx, x: Fx) -> Fx {
+    if y == Fx::ZERO {
+        return if x < Fx::ZERO { PI } else { Fx::ZERO };
+    }
+    let biggest = x.abs().max(y.abs());
+    // Scaled down first so nothing overflows while it spins
+    let (mut x, mut y) = (x / biggest, y / biggest);
+    let mut z = Fx::ZERO;
+    if x < Fx::ZERO {
+        z = if y >= Fx::ZERO { PI } else { -PI };
+        (x, y) = (-x, -y);
+    }
+    for (ix, atan) in ATANS.iter().enumerate() {
+        let (dx, dy) = (y >> ix as u32, x >> ix as u32);
+        if y > Fx::ZERO {
+            (x, y, z) = (x + dx, y - dy, z + *atan);
+        } else {
+            (x, y, z) = (x - dx, y + dy, z - *atan);
+        }
+    }
+    z
+}
+
+pub fn atan(x: Fx) -> Fx {
+    atan2(x, Fx::ONE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fx;
+
+    /// How far off from f64 anything is allowed to be
+    const MAX_ERROR: f64 = 1e-8;
+
+    fn assert_close(actual: Fx, expected: f64) {
+        let error = (actual.to_num::<f64>() - expected).abs();
+        assert!(
+            error <= MAX_ERROR,
+            "{actual} is {error} away from {expected}"
+        );
+    }
+
+    #[test]
+    fn atan_table_and_gain_match_f64() {
+        for (ix, atan) in ATANS.iter().enumerate() {
+            let expected = (2f64.powi(-(ix as i32))).atan() * 2f64.powi(32);
+            assert!(
+                (atan.to_bits() as f64 - expected).abs() <= 0.5,
+                "ATANS[{ix}]"
+            );
+        }
+        let gain = (0..ITERATIONS as i32)
+            .map(|ix| 1.0 / (1.0 + 2f64.powi(-2 * ix)).sqrt())
+            .product::<f64>();
+        assert!((INV_GAIN.to_bits() as f64 - gain * 2f64.powi(32)).abs() <= 0.5);
+    }
+
+    #[test]
+    fn sin_cos_on_the_axes() {
+        for (angle, sin, cos) in [
+            (Fx::ZERO, 0.0, 1.0),
+            (FRAC_PI_2, 1.0, 0.0),
+            (PI, 0.0, -1.0),
+            (-FRAC_PI_2, -1.0, 0.0),
+            (-PI, 0.0, -1.0),
+            (TAU, 0.0, 1.0),
+        ] {
+            let (actual_sin, actual_cos) = sin_cos(angle);
+            assert_close(actual_sin, sin);
+            assert_close(actual_cos, cos);
+        }
+    }
+
+    #[test]
+    fn sin_cos_match_f64() {
+        // Hundredths of a radian, a few times around the circle both ways
+        for hundredths in -2000..=2000 {
+            let angle = fx!(hundredths) / 100;
+            let (sin, cos) = sin_cos(angle);
+            assert_close(sin, angle.to_num::<f64>().sin());
+            assert_close(cos, angle.to_num::<f64>().cos());
+        }
+    }
+
+    #[test]
+    fn atan2_on_the_axes_and_octant_boundaries() {
+        assert_eq!(atan2(Fx::ZERO, Fx::ZERO), Fx::ZERO);
+        assert_eq!(atan2(Fx::ZERO, fx!(-1)), PI);
+        for (y, x, expected) in [
+            (0, 1, 0.0),
+            (1, 0, std::f64::consts::FRAC_PI_2),
+            (-1, 0, -std::f64::consts::FRAC_PI_2),
+            (1, 1, std::f64::consts::FRAC_PI_4),
+            (1, -1, 3.0 * std::f64::consts::FRAC_PI_4),
+            (-1, -1, -3.0 * std::f64::consts::FRAC_PI_4),
+            (-1, 1, -std::f64::consts::FRAC_PI_4),
+        ] {
+            // Same answer no matter how big or small the point is
+            for scale in [Fx::DELTA, fx!(1), fx!(1000), fx!(1_000_000)] {
+                assert_close(atan2(fx!(y) * scale, fx!(x) * scale), expected);
+            }
+        }
+        // Just either side of -x, the answer flips between ±PI
+        assert_close(atan2(Fx::DELTA, fx!(-1)), std::f64::consts::PI);
+        assert_close(atan2(-Fx::DELTA, fx!(-1)), -std::f64::consts::PI);
+    }
+
+    #[test]
+    fn atan2_and_atan_match_f64() {
+        for radius in [fx!(1) / 1000, fx!(1), fx!(1000)] {
+            for hundredths in -314..=314 {
+                let angle = fx!(hundredths) / 100;
+                let (y, x) = (radius * sin(angle), radius * cos(angle));
+                assert_close(atan2(y, x), y.to_num::<f64>().atan2(x.to_num::<f64>()));
+            }
+        }
+        for tenths in -1000..=1000 {
+            let x = fx!(tenths) / 10;
+            assert_close(atan(x), x.to_num::<f64>().atan());
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use fixed::traits::ToFixed;
 
-use super::Fx;
+use super::{fmath, terp::TerpMode, terp::Terpable, Fx};
 
 #[macro_export]
 macro_rules! fx {
@@ -46,11 +46,112 @@ impl FVec2 {
     pub fn dot(&self, o: Self) -> Fx {
         self.x * o.x + self.y * o.y
     }
+    /// The z of the 3d cross product. Positive if `o` is counter-clockwise from us.
+    pub fn perp_dot(&self, o: Self) -> Fx {
+        self.x * o.y - self.y * o.x
+    }
+    /// Rotated 90° counter-clockwise
+    pub fn perp(&self) -> Self {
+        Self::const_new(-self.y, self.x)
+    }
+    pub fn distance(&self, o: Self) -> Fx {
+        (*self - o).length()
+    }
+
+    /// Length one in the same direction, or `None` for zero.
+    /// Scales down first so big vectors don't overflow when squared.
+    pub fn try_normalize(&self) -> Option<Self> {
+        let biggest = self.x.abs().max(self.y.abs());
+        if biggest == Fx::ZERO {
+            return None;
+        }
+        let scaled = *self / biggest;
+        Some(scaled / scaled.length())
+    }
+    pub fn normalize_or_zero(&self) -> Self {
+        self.try_normalize().unwrap_or_default()
+    }
 
+    /// Unit vector pointing at this angle (radians, counter-clockwise from +x)
+    pub fn from_angle(angle: Fx) -> Self {
+        let (sin, cos) = fmath::sin_cos(angle);
+        Self::const_new(cos, sin)
+    }
+    /// Angle from +x, in (-PI, PI]
+    pub fn angle(&self) -> Fx {
+        fmath::atan2(self.y, self.x)
+    }
+    /// Signed angle to get from us to `o`, in (-PI, PI]
+    pub fn angle_to(&self, o: Self) -> Fx {
+        fmath::atan2(self.perp_dot(o), self.dot(o))
+    }
+    /// Rotated counter-clockwise by this many radians
+    pub fn rotate(&self, angle: Fx) -> Self {
+        let (sin, cos) = fmath::sin_cos(angle);
+        Self::const_new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    pub fn lerp(&self, o: Self, frac: Fx) -> Self {
+        *self + (o - *self) * frac
+    }
+    pub fn min(&self, o: Self) -> Self {
+        Self::const_new(self.x.min(o.x), self.y.min(o.y))
+    }
+    pub fn max(&self, o: Self) -> Self {
+        Self::const_new(self.x.max(o.x), self.y.max(o.y))
+    }
+    pub fn clamp(&self, min: Self, max: Self) -> Self {
+        Self::const_new(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
+    }
+    pub fn abs(&self) -> Self {
+        Self::const_new(self.x.abs(), self.y.abs())
+    }
+    /// The part of us that points along `o`. Zero if `o` is zero.
+    pub fn project_onto(&self, o: Self) -> Self {
+        match o.try_normalize() {
+            Some(dir) => dir * self.dot(dir),
+            None => Self::ZERO,
+        }
+    }
+    /// Bounced off a surface with this normal. The normal should be length one.
+    pub fn reflect(&self, normal: Self) -> Self {
+        *self - normal * (self.dot(normal) * 2)
+    }
+
+    pub fn floor(&self) -> IVec2 {
+        IVec2 {
+            x: self.x.floor().to_num(),
+            y: self.y.floor().to_num(),
+        }
+    }
     pub fn as_vec2(&self) -> Vec2 {
         Vec2::new(self.x.to_num(), self.y.to_num())
     }
 }
+impl From<IVec2> for FVec2 {
+    fn from(value: IVec2) -> Self {
+        Self::new(value.x, value.y)
+    }
+}
+/// NOTE: The conversion itself is exact, but anything that made the `Vec2` probably wasn't deterministic
+impl From<Vec2> for FVec2 {
+    fn from(value: Vec2) -> Self {
+        Self::new(value.x, value.y)
+    }
+}
+impl From<FVec2> for Vec2 {
+    fn from(value: FVec2) -> Self {
+        value.as_vec2()
+    }
+}
+impl Terpable for FVec2 {
+    fn terp(start: &Self, stop: &Self, mode: &TerpMode, frac: Fx) -> Self {
+        Self::const_new(
+            Fx::terp(&start.x, &stop.x, mode, frac),
+            Fx::terp(&start.y, &stop.y, mode, frac),
+        )
+    }
+}
 impl PartialEq for FVec2 {
     fn eq(&self, other: &Self) -> bool {
         self.x == other.x && self.y == other.y
@@ -73,6 +174,16 @@ impl std::ops::AddAssign for FVec2 {
         self.y += rhs.y;
     }
 }
+impl std::ops::Neg for FVec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
 impl std::ops::Sub for FVec2 {
     type Output = Self;
 
@@ -117,6 +228,24 @@ impl std::ops::Mul<FVec2> for Fx {
     }
 }
 
+/// Component-wise
+impl std::ops::Mul for FVec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x * rhs.x,
+            y: self.y * rhs.y,
+        }
+    }
+}
+impl std::ops::MulAssign for FVec2 {
+    fn mul_assign(&mut self, rhs: Self) {
+        self.x *= rhs.x;
+        self.y *= rhs.y;
+    }
+}
+
 impl std::ops::Div<Fx> for FVec2 {
     type Output = Self;
 
@@ -143,3 +272,68 @@ impl std::ops::Div<FVec2> for Fx {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: FVec2, expected: FVec2) {
+        let error = (actual - expected).length();
+        assert!(
+            error < fx!(1) / 1_000_000,
+            "{actual:?} is {error} away from {expected:?}"
+        );
+    }
+
+    #[test]
+    fn rotating_and_angles() {
+        assert_close(FVec2::X.rotate(fmath::FRAC_PI_2), FVec2::Y);
+        assert_close(FVec2::new(3, 4).rotate(fmath::PI), FVec2::new(-3, -4));
+        assert_close(FVec2::new(3, 4).rotate(-fmath::TAU), FVec2::new(3, 4));
+        assert_close(FVec2::from_angle(-fmath::FRAC_PI_2), FVec2::new(0, -1));
+        assert_eq!(FVec2::ZERO.angle(), Fx::ZERO);
+        assert_eq!(FVec2::new(-2, 0).angle(), fmath::PI);
+        let angle = FVec2::new(0, -2).angle();
+        assert!((angle + fmath::FRAC_PI_2).abs() < fx!(1) / 1_000_000);
+        let to = FVec2::X.angle_to(FVec2::Y);
+        assert!((to - fmath::FRAC_PI_2).abs() < fx!(1) / 1_000_000);
+    }
+
+    #[test]
+    fn reflecting_and_projecting() {
+        assert_eq!(FVec2::new(1, -1).reflect(FVec2::Y), FVec2::new(1, 1));
+        assert_eq!(FVec2::new(3, -2).reflect(FVec2::X), FVec2::new(-3, -2));
+        assert_eq!(
+            FVec2::new(3, 4).project_onto(FVec2::new(2, 0)),
+            FVec2::new(3, 0)
+        );
+        assert_close(
+            FVec2::new(2, 0).project_onto(FVec2::new(1, 1)),
+            FVec2::new(1, 1),
+        );
+        assert_eq!(FVec2::new(3, 4).project_onto(FVec2::ZERO), FVec2::ZERO);
+    }
+
+    #[test]
+    fn normalizing_tiny_and_huge_vecs() {
+        assert!(FVec2::ZERO.try_normalize().is_none());
+        assert_eq!(FVec2::ZERO.normalize_or_zero(), FVec2::ZERO);
+        // Squaring these would underflow (or overflow) without scaling them first
+        assert_eq!(
+            FVec2::const_new(Fx::DELTA, Fx::ZERO).try_normalize(),
+            Some(FVec2::X)
+        );
+        assert_close(
+            FVec2::const_new(-Fx::DELTA, Fx::DELTA).normalize_or_zero(),
+            FVec2::new(-1, 1) / fx!(2).sqrt(),
+        );
+        assert_close(
+            FVec2::new(1_000_000, 1_000_000).normalize_or_zero(),
+            FVec2::ONE / fx!(2).sqrt(),
+        );
+        assert_close(
+            FVec2::new(0, -40_000).normalize_or_zero(),
+            FVec2::new(0, -1),
+        );
+    }
+}
@@ -110,6 +110,23 @@ impl Input {
         debug_assert!(!self.combo_map.contains_key(&key));
         self.combo_map.insert(key, Box::new(trigger));
     }
+
+    /// Holds (or lets go of) a butt without going through a real device
+    #[cfg(test)]
+    pub(crate) fn set_pressed(&mut self, butt: Butt, pressed: bool) {
+        self.butts.0.insert(
+            butt,
+            PressData {
+                pressed,
+                ..default()
+            },
+        );
+    }
+    /// Points a stick without going through a real device
+    #[cfg(test)]
+    pub(crate) fn set_stick(&mut self, stick: Stick, dir: Vec2) {
+        self.sticks.0.insert(stick, dir);
+    }
 }
 impl std::fmt::Debug for Input {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
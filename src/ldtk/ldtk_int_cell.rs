@@ -1,10 +1,15 @@
 use bevy::prelude::*;
-use bevy_ecs_ldtk::{app::LdtkIntCellAppExt, ldtk::LayerInstance, IntGridCell};
+use bevy_ecs_ldtk::{
+    app::LdtkIntCellAppExt,
+    ldtk::LayerInstance,
+    prelude::{LayerMetadata, LdtkProject, LdtkProjectHandle, LevelIid, LevelMetadataAccessor},
+    IntGridCell,
+};
 use bevy_ecs_tilemap::map::TilemapType;
 
 use crate::{
     fx,
-    glue::aabbify::{aabbify_make_hollow, aabify_consolidate, Pixel},
+    glue::aabbify::{aabbify_make_hollow, aabify_consolidate, Key, Pixel},
     prelude::*,
 };
 
@@ -48,15 +53,52 @@ struct LayerHandled;
 #[derive(Resource)]
 pub struct LdtkIntCellConsolidate<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
     grid_size: u32,
+    /// Every consolidated cell
+    cells: HashMap<Key, Entity>,
+    keys: HashMap<Entity, Key>,
+    /// Cell -> the cell holding the StaticTx for its group. Hollowed out cells aren't in here.
+    holders: HashMap<Entity, Entity>,
+    /// Holder -> every cell in its group
+    groups: HashMap<Entity, Vec<Entity>>,
+    /// Cells that were added or removed since the last consolidation
+    dirty: HashSet<Key>,
     _pd: std::marker::PhantomData<(R, B)>,
 }
 impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> LdtkIntCellConsolidate<R, B> {
     pub fn grid_size(grid_size: u32) -> Self {
         Self {
             grid_size,
+            cells: default(),
+            keys: default(),
+            holders: default(),
+            groups: default(),
+            dirty: default(),
             _pd: default(),
         }
     }
+    /// The consolidated cell at this spot, if there is one
+    pub fn get_cell(&self, pos: Pos) -> Option<Entity> {
+        self.cells
+            .get(&Key::from_pos(self.snap(pos), self.grid_size))
+            .copied()
+    }
+    /// Rounds to the center of the grid cell this pos is in
+    fn snap(&self, pos: Pos) -> Pos {
+        let grid_size = fx!(self.grid_size);
+        Pos::new(
+            (pos.x / grid_size).round() * grid_size,
+            (pos.y / grid_size).round() * grid_size,
+        )
+        .with_z(pos.z)
+    }
+}
+
+/// The original (single cell) collider of a consolidated cell, so it can be put back when re-consolidating
+#[derive(Component)]
+struct LdtkIntCellTemplate<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
+    stx: StaticTx,
+    occlude: Option<OccludeLight>,
+    _pd: std::marker::PhantomData<(R, B)>,
 }
 
 #[derive(Component)]
@@ -69,6 +111,204 @@ impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> Default for LdtkNeedsConsolidation
     }
 }
 
+/// What a tiled cell of some value turns into. Read off the value's bundle once, at startup.
+#[derive(Clone, Copy, Debug)]
+struct LdtkTileTemplate {
+    kind: StaticTxKind,
+    layers: PhysicsLayers,
+    occlude: bool,
+}
+
+/// Where the cells of a value live when using `with_tile_colliders`.
+/// Cells are read straight out of the int grid into a `TileGrid`, no entity per cell.
+#[derive(Resource)]
+struct LdtkIntCellTiles<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
+    layer_id: &'static str,
+    cell_size: u32,
+    /// Every value that goes in the grid (so not slopes), and what it becomes
+    values: Vec<i32>,
+    templates: HashMap<i32, LdtkTileTemplate>,
+    /// The `TileGridCtrl` of the grid. A new one is spawned if this one (or its grid) is gone.
+    ctrl: Option<Entity>,
+    /// Cell -> the layer entity that filled it (or the ctrl, if it was added at runtime)
+    owners: HashMap<IVec2, Entity>,
+    /// Cells that block light
+    occluding: HashSet<IVec2>,
+    /// Occluding cell -> the occluder covering its group. Hollowed out cells aren't in here.
+    occluders: HashMap<IVec2, Entity>,
+    /// Occluder -> every cell in its group
+    occluder_groups: HashMap<Entity, Vec<IVec2>>,
+    /// Cells that were added or removed since the occluders were last rebuilt
+    dirty: HashSet<IVec2>,
+    _pd: std::marker::PhantomData<(R, B)>,
+}
+impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> LdtkIntCellTiles<R, B> {
+    fn new(layer_id: &'static str, cell_size: u32, values: Vec<i32>) -> Self {
+        Self {
+            layer_id,
+            cell_size,
+            values,
+            templates: default(),
+            ctrl: None,
+            owners: default(),
+            occluding: default(),
+            occluders: default(),
+            occluder_groups: default(),
+            dirty: default(),
+            _pd: default(),
+        }
+    }
+    /// The grid cell at this spot, if there's a (live) grid
+    fn cell_at(&self, tiles: &TileColliders, pos: Pos) -> Option<(Entity, IVec2)> {
+        let ctrl = self.ctrl?;
+        Some((ctrl, tiles.get_grid(ctrl)?.cell_at(pos.as_fvec2())))
+    }
+    /// The ctrl of the grid, spawning it (and starting over) if it's gone
+    fn get_or_spawn_ctrl(
+        &mut self,
+        tiles: &mut TileColliders,
+        roots: &LdtkRootResGeneric<R>,
+        commands: &mut Commands,
+    ) -> Entity {
+        if let Some(ctrl) = self.ctrl.filter(|ctrl| tiles.get_grid(*ctrl).is_some()) {
+            return ctrl;
+        }
+        // Occluders are children of the old ctrl, so they went with it
+        self.owners.clear();
+        self.occluding.clear();
+        self.occluders.clear();
+        self.occluder_groups.clear();
+        self.dirty.clear();
+        let ctrl = commands
+            .spawn((
+                Name::new("LdtkIntCellTiles"),
+                TileGridCtrl,
+                Transform::default(),
+                Visibility::default(),
+                ChildOf(roots.get_eid(B::ROOT)),
+            ))
+            .id();
+        // Cell centers from LDTK are on multiples of the cell size
+        let half = fx!(self.cell_size) / 2;
+        let layers = self
+            .templates
+            .values()
+            .next()
+            .map(|template| template.layers)
+            .unwrap_or_default();
+        tiles.insert_grid(
+            ctrl,
+            TileGrid::new(self.cell_size)
+                .with_origin(-half, -half)
+                .with_layers(layers),
+        );
+        self.ctrl = Some(ctrl);
+        ctrl
+    }
+    /// Fills the cell at `pos` with a cell of this value. Returns false if there was already one there.
+    fn add_cell(
+        &mut self,
+        tiles: &mut TileColliders,
+        pos: Pos,
+        value: i32,
+        owner: Option<Entity>,
+        roots: &LdtkRootResGeneric<R>,
+        commands: &mut Commands,
+    ) -> bool {
+        let Some(template) = self.templates.get(&value).copied() else {
+            return true;
+        };
+        let ctrl = self.get_or_spawn_ctrl(tiles, roots, commands);
+        let grid = tiles.get_grid_mut(ctrl).expect("ctrl has a grid");
+        let cell = grid.cell_at(pos.as_fvec2());
+        if grid.get(cell).is_some() {
+            return false;
+        }
+        grid.set(cell, Some(template.kind));
+        self.owners.insert(cell, owner.unwrap_or(ctrl));
+        if template.occlude {
+            self.occluding.insert(cell);
+            self.dirty.insert(cell);
+        }
+        true
+    }
+    /// Empties a cell. Returns false if there was nothing there.
+    fn remove_cell(&mut self, tiles: &mut TileColliders, cell: IVec2) -> bool {
+        let Some(grid) = self.ctrl.and_then(|ctrl| tiles.get_grid_mut(ctrl)) else {
+            return false;
+        };
+        if grid.get(cell).is_none() {
+            return false;
+        }
+        grid.set(cell, None);
+        self.owners.remove(&cell);
+        if self.occluding.remove(&cell) {
+            self.dirty.insert(cell);
+        }
+        true
+    }
+}
+
+/// On the int grid layers whose cells have been put in the grid
+#[derive(Component)]
+struct LdtkTilesFilled<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
+    _pd: std::marker::PhantomData<(R, B)>,
+}
+
+/// How a slope int-cell fills its cell. The corner is where the right angle of the ramp sits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LdtkSlope {
+    /// A 45° ramp filling the whole cell
+    Ramp45(HBoxCorner),
+    /// The thin end of a 2:1 ramp spanning two cells
+    Ramp22Low(HBoxCorner),
+    /// The thick end of a 2:1 ramp spanning two cells
+    Ramp22High(HBoxCorner),
+}
+impl LdtkSlope {
+    /// Turns the hbox of a full cell into the hboxes for this slope
+    fn cut(&self, cell: &HBox) -> Vec<HBox> {
+        let size = cell.get_size();
+        let offset = cell.get_offset();
+        let marker = cell.get_marker();
+        let (Self::Ramp45(corner) | Self::Ramp22Low(corner) | Self::Ramp22High(corner)) = *self;
+        // Which way (vertically) the flat side of the ramp is
+        let flat_dir = match corner {
+            HBoxCorner::BottomLeft | HBoxCorner::BottomRight => fx!(-1),
+            HBoxCorner::TopLeft | HBoxCorner::TopRight => fx!(1),
+        };
+        let quarter_h = fx!(size.y) / 4;
+        match self {
+            Self::Ramp45(_) => vec![HBox::right_tri(size.x, size.y, corner)
+                .with_offset(offset.x, offset.y)
+                .with_marker(marker)],
+            Self::Ramp22Low(_) => vec![HBox::right_tri(size.x, size.y / 2, corner)
+                .with_offset(offset.x, offset.y + flat_dir * quarter_h)
+                .with_marker(marker)],
+            Self::Ramp22High(_) => vec![
+                HBox::new(size.x, size.y / 2)
+                    .with_offset(offset.x, offset.y + flat_dir * quarter_h)
+                    .with_marker(marker),
+                HBox::right_tri(size.x, size.y / 2, corner)
+                    .with_offset(offset.x, offset.y - flat_dir * quarter_h)
+                    .with_marker(marker),
+            ],
+        }
+    }
+}
+
+#[derive(Resource)]
+struct LdtkIntCellSlopes<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
+    map: HashMap<i32, LdtkSlope>,
+    _pd: std::marker::PhantomData<(R, B)>,
+}
+
+#[derive(Component)]
+struct LdtkNeedsSlope<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
+    slope: LdtkSlope,
+    _pd: std::marker::PhantomData<(R, B)>,
+}
+
 fn post_ldtk_int_cell_layer_blessing(
     layer_info: Res<LdtkIntCellLayerInfo>,
     layer_q: Query<(Entity, &Name), (With<TilemapType>, Without<LayerHandled>)>,
@@ -84,11 +324,40 @@ fn post_ldtk_int_cell_layer_blessing(
     }
 }
 
+/// Spawns the bundle for an int cell, and marks it for slope cutting or consolidation if needed
+fn spawn_int_cell<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    commands: &mut Commands,
+    pos: Pos,
+    value: i32,
+    roots: &LdtkRootResGeneric<R>,
+    consolidate: bool,
+    slopes: Option<&LdtkIntCellSlopes<R, B>>,
+) -> Entity {
+    let bund = B::from_ldtk(pos, value);
+    let spawned_eid = commands
+        .spawn(bund)
+        .insert(ChildOf(roots.get_eid(B::ROOT)))
+        .id();
+    if let Some(slope) = slopes.and_then(|slopes| slopes.map.get(&value)) {
+        // Slopes don't get consolidated, they stay their own little triangles
+        commands.entity(spawned_eid).insert(LdtkNeedsSlope::<R, B> {
+            slope: *slope,
+            _pd: default(),
+        });
+    } else if consolidate {
+        commands
+            .entity(spawned_eid)
+            .insert(LdtkNeedsConsolidation::<R, B>::default());
+    }
+    spawned_eid
+}
+
 fn post_ldtk_int_cell_value_blessing<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
     mut commands: Commands,
     mut wrappers: Query<(Entity, &GlobalTransform, &LdtkIntCellWrapper<R, B>)>,
     roots: Res<LdtkRootResGeneric<R>>,
     maybe_consolidate: Option<Res<LdtkIntCellConsolidate<R, B>>>,
+    maybe_slopes: Option<Res<LdtkIntCellSlopes<R, B>>>,
 ) {
     for (ldtk_eid, gt, wrapper) in &mut wrappers {
         if gt.translation().x == 0.0 && gt.translation().y == 0.0 {
@@ -99,79 +368,508 @@ fn post_ldtk_int_cell_value_blessing<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
             fx!(gt.translation().x.round() as i32),
             fx!(gt.translation().y.round() as i32),
         );
-        let bund = B::from_ldtk(pos, wrapper.value);
-        let spawned_eid = commands
-            .spawn(bund)
-            .insert(ChildOf(roots.get_eid(B::ROOT)))
-            .id();
+        spawn_int_cell(
+            &mut commands,
+            pos,
+            wrapper.value,
+            &roots,
+            maybe_consolidate.is_some(),
+            maybe_slopes.as_deref(),
+        );
         commands
             .entity(ldtk_eid)
             .remove::<LdtkIntCellWrapper<R, B>>();
+    }
+}
 
-        if maybe_consolidate.is_some() {
-            commands
-                .entity(spawned_eid)
-                .insert(LdtkNeedsConsolidation::<R, B>::default());
+/// Adds newly spawned cells to the grid. They get consolidated (along with their neighbors) right after.
+fn ldtk_int_cell_consolidate<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    mut consolidate_res: ResMut<LdtkIntCellConsolidate<R, B>>,
+    needs_consolidation: Query<
+        (Entity, &Pos, &StaticTx, Option<&OccludeLight>),
+        With<LdtkNeedsConsolidation<R, B>>,
+    >,
+    mut commands: Commands,
+) {
+    for (eid, pos, stx, occlude) in &needs_consolidation {
+        debug_assert!(stx.comps.len() == 1);
+        // If we're providing a custom occlude here, we're gonna be f'd (unless I were smarter)
+        debug_assert!(occlude.is_none() || matches!(occlude, Some(OccludeLight::StaticTx)));
+        let key = Key::from_pos(*pos, consolidate_res.grid_size);
+        if let Some(old_eid) = consolidate_res.cells.insert(key.clone(), eid) {
+            warn!("Two consolidated int cells in the same spot, forgetting {old_eid:?}");
+            consolidate_res.keys.remove(&old_eid);
         }
+        consolidate_res.keys.insert(eid, key.clone());
+        consolidate_res.dirty.insert(key);
+        commands
+            .entity(eid)
+            .remove::<LdtkNeedsConsolidation<R, B>>()
+            .insert(LdtkIntCellTemplate::<R, B> {
+                stx: stx.clone(),
+                occlude: occlude.cloned(),
+                _pd: default(),
+            });
     }
 }
 
-fn ldtk_int_cell_consolidate<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
-    consolidate_res: Res<LdtkIntCellConsolidate<R, B>>,
-    needs_consolidation: Query<(Entity, &Pos), With<LdtkNeedsConsolidation<R, B>>>,
-    stability_q: Query<(&StaticTx, &OccludeLight), With<LdtkNeedsConsolidation<R, B>>>,
+/// Forgets cells that got despawned (dug out, level unloaded, etc.)
+fn ldtk_int_cell_forget_removed<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    mut consolidate_res: ResMut<LdtkIntCellConsolidate<R, B>>,
+    mut removed: RemovedComponents<LdtkIntCellTemplate<R, B>>,
+) {
+    for eid in removed.read() {
+        let Some(key) = consolidate_res.keys.remove(&eid) else {
+            continue;
+        };
+        if consolidate_res.cells.get(&key) == Some(&eid) {
+            consolidate_res.cells.remove(&key);
+        }
+        consolidate_res.dirty.insert(key);
+        // If it was holding the collider for a group, the rest of the group needs a new one
+        if let Some(holder) = consolidate_res.holders.remove(&eid) {
+            let member_keys = consolidate_res
+                .groups
+                .get(&holder)
+                .into_iter()
+                .flatten()
+                .filter_map(|member| consolidate_res.keys.get(member).cloned())
+                .collect::<Vec<_>>();
+            consolidate_res.dirty.extend(member_keys);
+        }
+    }
+}
+
+/// Reads what each tiled value turns into off its bundle. Spawns (and despawns) one bundle per value,
+/// so the bundle stays the one place that says what a cell is.
+fn ldtk_int_cell_tile_templates<R: LdtkRootKind, B: LdtkIntCellValue<R>>(world: &mut World) {
+    let values = world.resource::<LdtkIntCellTiles<R, B>>().values.clone();
+    let mut templates = HashMap::<i32, LdtkTileTemplate>::default();
+    for value in values {
+        let eid = world.spawn(B::from_ldtk(Pos::default(), value)).id();
+        let comp = world
+            .get::<StaticTx>(eid)
+            .and_then(|stx| stx.comps.first().cloned());
+        let occlude = world.get::<OccludeLight>(eid).is_some();
+        world.despawn(eid);
+        let Some(comp) = comp else {
+            warn!("Tiled int cell value {value} has no StaticTx, its cells won't collide");
+            continue;
+        };
+        if templates
+            .values()
+            .any(|template| template.layers != comp.layers)
+        {
+            warn!("Tiled int cells should all have the same layers, using the first for {value}");
+        }
+        templates.insert(
+            value,
+            LdtkTileTemplate {
+                kind: comp.kind,
+                layers: comp.layers,
+                occlude,
+            },
+        );
+    }
+    world.resource_mut::<LdtkIntCellTiles<R, B>>().templates = templates;
+}
+
+/// Puts the cells of newly spawned int grid layers in the grid, straight from the level's int grid
+fn ldtk_int_cell_fill_tiles<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    mut tiles_res: ResMut<LdtkIntCellTiles<R, B>>,
+    mut tiles: ResMut<TileColliders>,
+    layer_q: Query<
+        (Entity, &LayerMetadata, &GlobalTransform, &ChildOf),
+        Without<LdtkTilesFilled<R, B>>,
+    >,
+    level_q: Query<&LevelIid>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    roots: Res<LdtkRootResGeneric<R>>,
     mut commands: Commands,
 ) {
+    let Ok(project) = ldtk_projects.single() else {
+        return;
+    };
+    let Some(ldtk_project) = ldtk_project_assets.get(project) else {
+        return;
+    };
+    for (layer_eid, meta, gt, child_of) in &layer_q {
+        if meta.identifier != tiles_res.layer_id {
+            continue;
+        }
+        if gt.translation().x == 0.0 && gt.translation().y == 0.0 {
+            // Same as the wrapped cells, the transform isn't there until a frame after spawning
+            continue;
+        }
+        let Some(layer) = level_q
+            .get(child_of.parent())
+            .ok()
+            .and_then(|level_iid| ldtk_project.get_raw_level_by_iid(level_iid.get()))
+            .and_then(|level| level.layer_instances.as_ref())
+            .and_then(|layers| layers.iter().find(|layer| layer.iid == meta.iid))
+        else {
+            continue;
+        };
+        if layer.grid_size != tiles_res.cell_size as i32 {
+            warn!(
+                "Int grid layer {} has cells of size {}, but its tile colliders use {}",
+                meta.identifier, layer.grid_size, tiles_res.cell_size
+            );
+        }
+        commands
+            .entity(layer_eid)
+            .insert(LdtkTilesFilled::<R, B> { _pd: default() });
+        // The layer's transform is at the center of its bottom left cell, and LDTK's rows go down
+        let grid_size = layer.grid_size as f32;
+        for (ix, value) in layer.int_grid_csv.iter().enumerate() {
+            if !tiles_res.values.contains(value) {
+                continue;
+            }
+            let col = ix as i32 % layer.c_wid;
+            let row = layer.c_hei - 1 - ix as i32 / layer.c_wid;
+            let pos = Pos::new(
+                fx!((gt.translation().x + col as f32 * grid_size).round() as i32),
+                fx!((gt.translation().y + row as f32 * grid_size).round() as i32),
+            );
+            if !tiles_res.add_cell(
+                &mut tiles,
+                pos,
+                *value,
+                Some(layer_eid),
+                &roots,
+                &mut commands,
+            ) {
+                warn!("Two tiled int cells in the same spot, keeping the first at {pos:?}");
+            }
+        }
+    }
+}
+
+/// Empties the cells of layers that went away (level unloaded, etc.)
+fn ldtk_int_cell_forget_removed_tiles<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    mut tiles_res: ResMut<LdtkIntCellTiles<R, B>>,
+    mut tiles: ResMut<TileColliders>,
+    mut removed: RemovedComponents<LdtkTilesFilled<R, B>>,
+) {
+    let gone = removed.read().collect::<HashSet<_>>();
+    if gone.is_empty() {
+        return;
+    }
+    let cells = tiles_res
+        .owners
+        .iter()
+        .filter(|(_, owner)| gone.contains(*owner))
+        .map(|(cell, _)| *cell)
+        .collect::<Vec<_>>();
+    for cell in cells {
+        tiles_res.remove_cell(&mut tiles, cell);
+    }
+}
+
+/// Re-hollows and re-consolidates the occluders around any tiled cells that changed.
+/// Same idea as `ldtk_int_cell_reconsolidate`, but the cells are grid positions instead of entities.
+fn ldtk_int_cell_reocclude_tiles<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    mut tiles_res: ResMut<LdtkIntCellTiles<R, B>>,
+    tiles: Res<TileColliders>,
+    mut commands: Commands,
+) {
+    if tiles_res.dirty.is_empty() {
+        return;
+    }
+    let res = &mut *tiles_res;
+    let Some((ctrl, grid)) = res
+        .ctrl
+        .and_then(|ctrl| Some((ctrl, tiles.get_grid(ctrl)?)))
+    else {
+        res.dirty.clear();
+        return;
+    };
+    let neighbors = |cell: IVec2| {
+        [
+            cell,
+            cell - IVec2::X,
+            cell + IVec2::X,
+            cell + IVec2::Y,
+            cell - IVec2::Y,
+        ]
+    };
+
+    // Changing a cell can change whether its neighbors are hollow, so they (and their groups) go too
+    let mut region = HashSet::<IVec2>::default();
+    for cell in std::mem::take(&mut res.dirty) {
+        for near in neighbors(cell) {
+            match res.occluders.remove(&near) {
+                Some(occluder) => {
+                    let group = res.occluder_groups.remove(&occluder).unwrap_or_default();
+                    for member in &group {
+                        res.occluders.remove(member);
+                    }
+                    region.extend(group);
+                    if let Ok(mut ent_comms) = commands.get_entity(occluder) {
+                        ent_comms.despawn();
+                    }
+                }
+                None => {
+                    region.insert(near);
+                }
+            }
+        }
+    }
+    region.retain(|cell| res.occluding.contains(cell));
+
+    // Hollow, then group what's left into as few boxes as we can.
+    // Neighbors go in too so the edges of the region know what's around them.
     let shell_set = aabbify_make_hollow(
-        needs_consolidation
+        region
             .iter()
-            .map(|(eid, pos)| Pixel::from_pos(*pos, consolidate_res.grid_size, eid)),
+            .flat_map(|cell| neighbors(*cell))
+            .filter(|cell| res.occluding.contains(cell))
+            .map(|cell| Pixel::new(Key::from_cell(cell), cell)),
     );
-    for (eid, _) in &needs_consolidation {
-        if !shell_set.contains(&eid) {
-            commands.entity(eid).remove::<StaticTx>();
+    let shell = region
+        .iter()
+        .filter(|cell| shell_set.contains(*cell))
+        .map(|cell| Pixel::new(Key::from_cell(*cell), *cell))
+        .collect::<Vec<_>>();
+    let consolidated_groups: Vec<Vec<IVec2>> = aabify_consolidate(shell);
+
+    let cell_size = grid.get_cell_size();
+    for group in consolidated_groups {
+        let first = *group.first().unwrap();
+        let last = *group.last().unwrap();
+        let center = (grid.cell_center(first) + grid.cell_center(last)) / fx!(2);
+        let size = (last - first + IVec2::ONE).as_uvec2() * cell_size;
+        let occluder = commands
+            .spawn((
+                Name::new("LdtkIntCellOccluder"),
+                Pos::new(center.x, center.y),
+                OccludeLight::custom(vec![HBox::new(size.x, size.y)]),
+                ChildOf(ctrl),
+            ))
+            .id();
+        for member in &group {
+            res.occluders.insert(*member, occluder);
         }
-        commands
-            .entity(eid)
-            .remove::<LdtkNeedsConsolidation<R, B>>();
+        res.occluder_groups.insert(occluder, group);
     }
+}
 
-    let consolidated_groups: Vec<Vec<Entity>> = aabify_consolidate(
-        needs_consolidation
+/// Re-hollows and re-consolidates the area around any cells that changed.
+/// Only the groups touching a change get broken up, everything else is left alone.
+fn ldtk_int_cell_reconsolidate<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    mut consolidate_res: ResMut<LdtkIntCellConsolidate<R, B>>,
+    cell_q: Query<(&Pos, Has<StaticTx>, &LdtkIntCellTemplate<R, B>)>,
+    mut stx_hboxes: StaticTxHBoxes,
+    mut commands: Commands,
+) {
+    if consolidate_res.dirty.is_empty() {
+        return;
+    }
+    let res = &mut *consolidate_res;
+
+    // Changing a cell can change whether its neighbors are hollow, so they (and their groups) go too
+    let mut region = HashSet::<Entity>::default();
+    for key in std::mem::take(&mut res.dirty) {
+        for near in [key.clone(), key.left(), key.right(), key.up(), key.down()] {
+            let Some(eid) = res.cells.get(&near).copied() else {
+                continue;
+            };
+            match res.holders.get(&eid) {
+                Some(holder) => region.extend(res.groups.get(holder).into_iter().flatten()),
+                None => {
+                    region.insert(eid);
+                }
+            }
+        }
+    }
+    region.retain(|eid| res.keys.contains_key(eid));
+    for eid in &region {
+        if let Some(holder) = res.holders.remove(eid) {
+            res.groups.remove(&holder);
+        }
+    }
+
+    // Hollow, then group what's left into as few boxes as we can.
+    // Neighbors go in too so the edges of the region know what's around them.
+    let shell_set = aabbify_make_hollow(
+        region
             .iter()
-            .filter(|(eid, _)| shell_set.contains(eid))
-            .map(|(eid, pos)| Pixel::from_pos(*pos, consolidate_res.grid_size, eid)),
+            .flat_map(|eid| {
+                let key = &res.keys[eid];
+                [key.clone(), key.left(), key.right(), key.up(), key.down()]
+            })
+            .filter_map(|key| res.cells.get(&key).map(|eid| Pixel::new(key, *eid))),
     );
+    let shell = region
+        .iter()
+        .filter(|eid| shell_set.contains(*eid))
+        .map(|eid| Pixel::new(res.keys[eid].clone(), *eid))
+        .collect::<Vec<_>>();
+    let consolidated_groups: Vec<Vec<Entity>> = aabify_consolidate(shell);
+
+    let grid_size = fx!(res.grid_size);
+    let mut holding = HashSet::<Entity>::default();
     for group in consolidated_groups {
-        let first = group.first().unwrap().clone();
-        let last = group.last().unwrap().clone();
-        if first == last {
+        let first = *group.first().unwrap();
+        let last = *group.last().unwrap();
+        let (Ok((first_pos, _, _)), Ok((last_pos, _, _))) = (cell_q.get(first), cell_q.get(last))
+        else {
             continue;
+        };
+        let w = last_pos.x - first_pos.x + grid_size;
+        let h = last_pos.y - first_pos.y + grid_size;
+        let new_hbox = HBox::new(w.round().to_num(), h.round().to_num())
+            .with_offset(w / 2 - grid_size / 2, h / 2 - grid_size / 2);
+        let Ok((_, has_stx, template)) = cell_q.get(first) else {
+            continue;
+        };
+        if has_stx {
+            stx_hboxes.update(first, |hbox| {
+                *hbox = new_hbox.clone().with_marker(hbox.get_marker())
+            });
+        } else {
+            let new_stx = template
+                .stx
+                .reshaped(|hbox| vec![new_hbox.clone().with_marker(hbox.get_marker())]);
+            let mut ent_comms = commands.entity(first);
+            ent_comms.insert(new_stx);
+            if let Some(occlude) = template.occlude.clone() {
+                ent_comms.insert(occlude);
+            }
         }
-        let first_pos = needs_consolidation.get(first).unwrap().1.clone();
-        let last_pos = needs_consolidation.get(last).unwrap().1.clone();
-        let w = last_pos.x - first_pos.x + fx!(consolidate_res.grid_size);
-        let h = last_pos.y - first_pos.y + fx!(consolidate_res.grid_size);
-        let new_hbox = HBox::new(w.round().to_num(), h.round().to_num()).with_offset(
-            w / 2 - fx!(consolidate_res.grid_size) / 2,
-            h / 2 - fx!(consolidate_res.grid_size) / 2,
-        );
-        let existing_comps = &stability_q.get(first).unwrap().0.comps;
-        debug_assert!(existing_comps.len() == 1);
-        let existing_kind = existing_comps[0].kind;
-        let existing_occlude = stability_q.get(first).map(|pair| pair.1.clone()).ok();
-        // If we're providing a custom occlude here, we're gonna be f'd (unless I were smarter)
-        debug_assert!(
-            existing_occlude.is_none() || matches!(existing_occlude, Some(OccludeLight::StaticTx))
-        );
-        for inner_eid in &group {
-            commands.entity(*inner_eid).remove::<StaticTx>();
+        holding.insert(first);
+        for member in &group {
+            res.holders.insert(*member, first);
         }
-        commands
-            .entity(first)
-            .insert(StaticTx::single(existing_kind, new_hbox));
-        if let Some(existing_occlude) = existing_occlude {
-            commands.entity(first).insert(existing_occlude);
+        res.groups.insert(first, group);
+    }
+    // Everything else in the region is either hollow or covered by its group's holder
+    for eid in region {
+        if holding.contains(&eid) {
+            continue;
+        }
+        if let Ok((_, true, _)) = cell_q.get(eid) {
+            commands.entity(eid).remove::<StaticTx>();
+        }
+    }
+}
+
+/// Adds an int cell at runtime, re-consolidating around it.
+/// NOTE: Only spawns what the `LdtkIntCellValue` bundle does. Tiles drawn by the LDTK layer itself won't change.
+#[derive(Event)]
+pub struct AddLdtkIntCellGeneric<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
+    pos: Pos,
+    value: i32,
+    _pd: std::marker::PhantomData<(R, B)>,
+}
+impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> AddLdtkIntCellGeneric<R, B> {
+    pub fn new(pos: Pos, value: i32) -> Self {
+        Self {
+            pos,
+            value,
+            _pd: default(),
+        }
+    }
+}
+fn handle_add_ldtk_int_cell<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    trigger: On<AddLdtkIntCellGeneric<R, B>>,
+    mut commands: Commands,
+    roots: Res<LdtkRootResGeneric<R>>,
+    mut tiles: ResMut<TileColliders>,
+    maybe_consolidate: Option<Res<LdtkIntCellConsolidate<R, B>>>,
+    maybe_tiles: Option<ResMut<LdtkIntCellTiles<R, B>>>,
+    maybe_slopes: Option<Res<LdtkIntCellSlopes<R, B>>>,
+) {
+    let mut pos = trigger.event().pos;
+    let value = trigger.event().value;
+    if let Some(mut tiles_res) = maybe_tiles {
+        if tiles_res.values.contains(&value) {
+            if !tiles_res.add_cell(&mut tiles, pos, value, None, &roots, &mut commands) {
+                warn!("AddLdtkIntCell: there's already a cell at {pos:?}");
+            }
+            return;
+        }
+    }
+    if let Some(consolidate) = maybe_consolidate.as_ref() {
+        pos = consolidate.snap(pos);
+        if consolidate.get_cell(pos).is_some() {
+            warn!("AddLdtkIntCell: there's already a cell at {pos:?}");
+            return;
+        }
+    }
+    spawn_int_cell(
+        &mut commands,
+        pos,
+        trigger.event().value,
+        &roots,
+        maybe_consolidate.is_some(),
+        maybe_slopes.as_deref(),
+    );
+}
+
+/// Removes (despawns) the int cell at this spot at runtime, re-consolidating around it.
+/// Despawning the cell entity yourself works too.
+/// NOTE: Only works for values with `with_consolidate` or `with_tile_colliders`, since those keep track of where cells are.
+#[derive(Event)]
+pub struct RemoveLdtkIntCellGeneric<R: LdtkRootKind, B: LdtkIntCellValue<R>> {
+    pos: Pos,
+    _pd: std::marker::PhantomData<(R, B)>,
+}
+impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> RemoveLdtkIntCellGeneric<R, B> {
+    pub fn new(pos: Pos) -> Self {
+        Self {
+            pos,
+            _pd: default(),
+        }
+    }
+}
+fn handle_remove_ldtk_int_cell<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    trigger: On<RemoveLdtkIntCellGeneric<R, B>>,
+    mut commands: Commands,
+    mut tiles: ResMut<TileColliders>,
+    maybe_consolidate: Option<Res<LdtkIntCellConsolidate<R, B>>>,
+    maybe_tiles: Option<ResMut<LdtkIntCellTiles<R, B>>>,
+) {
+    let pos = trigger.event().pos;
+    match (maybe_consolidate, maybe_tiles) {
+        (_, Some(mut tiles_res)) => {
+            if let Some((_, cell)) = tiles_res.cell_at(&tiles, pos) {
+                tiles_res.remove_cell(&mut tiles, cell);
+            }
+        }
+        (Some(consolidate), None) => {
+            if let Some(eid) = consolidate.get_cell(pos) {
+                commands.entity(eid).despawn();
+            }
+        }
+        (None, None) => {
+            warn!(
+                "RemoveLdtkIntCell only works for int cells with consolidation or tile colliders"
+            );
+        }
+    }
+}
+
+fn ldtk_int_cell_slope<R: LdtkRootKind, B: LdtkIntCellValue<R>>(
+    needs_slope: Query<(
+        Entity,
+        &LdtkNeedsSlope<R, B>,
+        Option<&StaticTx>,
+        Option<&OccludeLight>,
+    )>,
+    mut commands: Commands,
+) {
+    for (eid, needs, stx, occlude) in &needs_slope {
+        commands.entity(eid).remove::<LdtkNeedsSlope<R, B>>();
+        let Some(stx) = stx else {
+            continue;
+        };
+        let new_stx = stx.reshaped(|hbox| needs.slope.cut(hbox));
+        commands.entity(eid).remove::<StaticTx>();
+        commands.entity(eid).insert(new_stx);
+        if let Some(occlude) = occlude {
+            commands.entity(eid).insert(occlude.clone());
         }
     }
 }
@@ -204,6 +902,11 @@ pub struct LdtkIntCellValuePluginGeneric<R: LdtkRootKind, B: LdtkIntCellValue<R>
     /// When set to Some(x), will consolidate hboxes assuming a grid size of x.
     /// This involves both hollowing and aabbifying.
     consolidate: Option<u32>,
+    /// When set to Some(x), cells go into a `TileGrid` with cells of size x instead of being `StaticTx`s.
+    /// Slopes still get their own `StaticTx`.
+    tile_colliders: Option<u32>,
+    /// Values that should be cut into slopes instead of being full cells
+    slopes: HashMap<i32, LdtkSlope>,
     _pd: std::marker::PhantomData<(R, B)>,
 }
 impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> LdtkIntCellValuePluginGeneric<R, B> {
@@ -212,6 +915,8 @@ impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> LdtkIntCellValuePluginGeneric<R, B
             layer_id,
             values: vec![value],
             consolidate: None,
+            tile_colliders: None,
+            slopes: default(),
             _pd: default(),
         }
     }
@@ -220,6 +925,8 @@ impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> LdtkIntCellValuePluginGeneric<R, B
             layer_id,
             values: values.collect(),
             consolidate: None,
+            tile_colliders: None,
+            slopes: default(),
             _pd: default(),
         }
     }
@@ -227,10 +934,35 @@ impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> LdtkIntCellValuePluginGeneric<R, B
         self.consolidate = Some(grid_size);
         self
     }
+    /// Puts every (non-slope) cell in `TileColliders`, read straight from the level's int grid,
+    /// instead of spawning the bundle for each one. Much faster to load and query for big levels.
+    /// Cells become full `cell_size` squares with the kind of the bundle's `StaticTx`, and should
+    /// all share the same layers. If the bundle occludes light, so do the cells, merged into as few
+    /// occluders as possible.
+    /// Can't be combined with `with_consolidate`, the grid doesn't need it.
+    pub fn with_tile_colliders(mut self, cell_size: u32) -> Self {
+        self.tile_colliders = Some(cell_size);
+        self
+    }
+    /// Marks a value as a slope. Its StaticTx hboxes will get cut into the given ramp shape.
+    pub fn with_slope(mut self, value: i32, slope: LdtkSlope) -> Self {
+        if !self.values.contains(&value) {
+            self.values.push(value);
+        }
+        self.slopes.insert(value, slope);
+        self
+    }
 }
 impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> Plugin for LdtkIntCellValuePluginGeneric<R, B> {
     fn build(&self, app: &mut App) {
-        for value in &self.values {
+        assert!(
+            self.tile_colliders.is_none() || self.consolidate.is_none(),
+            "with_tile_colliders and with_consolidate can't be combined"
+        );
+        // Tiled values never get an entity, they're read straight from the layer
+        let is_tiled =
+            |value: &i32| self.tile_colliders.is_some() && !self.slopes.contains_key(value);
+        for value in self.values.iter().filter(|value| !is_tiled(value)) {
             app.register_ldtk_int_cell_for_layer::<LdtkIntCellWrapper<R, B>>(
                 &self.layer_id,
                 *value,
@@ -240,12 +972,50 @@ impl<R: LdtkRootKind, B: LdtkIntCellValue<R>> Plugin for LdtkIntCellValuePluginG
             Update,
             post_ldtk_int_cell_value_blessing::<R, B>.in_set(LdtkSet),
         );
+        app.add_observer(handle_add_ldtk_int_cell::<R, B>);
+        app.add_observer(handle_remove_ldtk_int_cell::<R, B>);
 
+        if !self.slopes.is_empty() {
+            app.insert_resource(LdtkIntCellSlopes::<R, B> {
+                map: self.slopes.clone(),
+                _pd: default(),
+            });
+            app.add_systems(
+                Update,
+                ldtk_int_cell_slope::<R, B>
+                    .in_set(LdtkSet)
+                    .after(post_ldtk_int_cell_value_blessing::<R, B>),
+            );
+        }
+
+        if let Some(cell_size) = self.tile_colliders {
+            app.insert_resource(LdtkIntCellTiles::<R, B>::new(
+                self.layer_id,
+                cell_size,
+                self.values.iter().copied().filter(is_tiled).collect(),
+            ));
+            app.add_systems(Startup, ldtk_int_cell_tile_templates::<R, B>);
+            app.add_systems(
+                Update,
+                (
+                    ldtk_int_cell_fill_tiles::<R, B>,
+                    ldtk_int_cell_forget_removed_tiles::<R, B>,
+                    ldtk_int_cell_reocclude_tiles::<R, B>,
+                )
+                    .chain()
+                    .in_set(LdtkSet),
+            );
+        }
         if let Some(grid_size) = self.consolidate {
             app.insert_resource(LdtkIntCellConsolidate::<R, B>::grid_size(grid_size));
             app.add_systems(
                 Update,
-                ldtk_int_cell_consolidate::<R, B>
+                (
+                    ldtk_int_cell_consolidate::<R, B>,
+                    ldtk_int_cell_forget_removed::<R, B>,
+                    ldtk_int_cell_reconsolidate::<R, B>,
+                )
+                    .chain()
                     .in_set(LdtkSet)
                     .after(post_ldtk_int_cell_value_blessing::<R, B>),
             );
@@ -257,3 +1027,196 @@ pub(super) fn register_ldtk_int_cell(app: &mut App) {
     app.insert_resource(LdtkIntCellLayerInfo::default());
     app.add_systems(Update, post_ldtk_int_cell_layer_blessing.in_set(LdtkSet));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::test_utils::{step, test_app};
+
+    #[derive(
+        Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash, Reflect, strum_macros::EnumIter,
+    )]
+    enum TestRoot {
+        #[default]
+        Dirt,
+    }
+    impl LdtkRootKind for TestRoot {}
+
+    #[derive(Bundle)]
+    struct DirtBundle {
+        pos: Pos,
+        stx: StaticTx,
+        occlude: OccludeLight,
+    }
+    impl LdtkIntCellValue<TestRoot> for DirtBundle {
+        const ROOT: TestRoot = TestRoot::Dirt;
+        fn from_ldtk(pos: Pos, _value: i32) -> Self {
+            Self {
+                pos,
+                stx: StaticTx::single(StaticTxKind::Solid, HBox::new(8, 8)),
+                occlude: OccludeLight::StaticTx,
+            }
+        }
+    }
+
+    type DirtPlugin = LdtkIntCellValuePluginGeneric<TestRoot, DirtBundle>;
+    type AddDirt = AddLdtkIntCellGeneric<TestRoot, DirtBundle>;
+    type RemoveDirt = RemoveLdtkIntCellGeneric<TestRoot, DirtBundle>;
+
+    /// Physics plus a dirt layer, no LDTK project loaded, so cells only come from events
+    fn dirt_app(plugin: DirtPlugin) -> App {
+        let mut app = test_app(default());
+        app.insert_resource(Assets::<LdtkProject>::default());
+        super::super::ldtk_roots::register_ldtk_root::<TestRoot>(&mut app);
+        app.add_plugins(plugin);
+        step(&mut app, 1);
+        app
+    }
+
+    /// Adds dirt to every cell of a `w` by `h` block, bottom left cell centered on the origin
+    fn add_block(app: &mut App, w: i32, h: i32) {
+        for x in 0..w {
+            for y in 0..h {
+                app.world_mut()
+                    .trigger(AddDirt::new(Pos::new(x * 8, y * 8), 1));
+            }
+        }
+        step(app, 1);
+    }
+
+    /// The world space occlusion boxes of the dirt
+    fn occluder_boxes(app: &mut App) -> Vec<HBox> {
+        app.world_mut()
+            .query::<(&Pos, &OccludeLight)>()
+            .iter(app.world())
+            .flat_map(|(pos, occlude)| match occlude {
+                OccludeLight::Custom { hboxes } => hboxes
+                    .iter()
+                    .map(|hbox| hbox.translated(pos.as_fvec2()))
+                    .collect(),
+                OccludeLight::StaticTx => vec![],
+            })
+            .collect()
+    }
+
+    /// Which cells of a `w` by `h` block are behind an occluder, bottom row first
+    fn covered_cells(boxes: &[HBox], w: i32, h: i32) -> Vec<Vec<bool>> {
+        (0..h)
+            .map(|y| {
+                (0..w)
+                    .map(|x| {
+                        let center = FVec2::new(fx!(x * 8), fx!(y * 8));
+                        boxes.iter().any(|hbox| {
+                            hbox.min_x() < center.x
+                                && center.x < hbox.max_x()
+                                && hbox.min_y() < center.y
+                                && center.y < hbox.max_y()
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The world space collision boxes of the dirt, left to right
+    fn stx_boxes(app: &mut App) -> Vec<HBox> {
+        let mut boxes = app
+            .world_mut()
+            .query::<(&Pos, &StaticTx)>()
+            .iter(app.world())
+            .flat_map(|(pos, stx)| stx.get_thboxes(*pos))
+            .collect::<Vec<_>>();
+        boxes.sort_by_key(|hbox| hbox.min_x());
+        boxes
+    }
+
+    #[test]
+    fn consolidated_cells_split_and_merge() {
+        let mut app = dirt_app(DirtPlugin::single("Dirt", 1).with_consolidate(8));
+        add_block(&mut app, 3, 1);
+        let sizes = |boxes: Vec<HBox>| {
+            boxes
+                .iter()
+                .map(|hbox| (hbox.min_x(), hbox.get_size()))
+                .collect::<Vec<_>>()
+        };
+        let whole = vec![(fx!(-4), UVec2::new(24, 8))];
+        assert_eq!(sizes(stx_boxes(&mut app)), whole);
+
+        // Digging out the middle splits it in two
+        app.world_mut().trigger(RemoveDirt::new(Pos::new(8, 0)));
+        step(&mut app, 1);
+        assert_eq!(
+            sizes(stx_boxes(&mut app)),
+            vec![(fx!(-4), UVec2::new(8, 8)), (fx!(12), UVec2::new(8, 8))]
+        );
+
+        // Filling it back in joins them up again
+        app.world_mut().trigger(AddDirt::new(Pos::new(8, 0), 1));
+        step(&mut app, 1);
+        assert_eq!(sizes(stx_boxes(&mut app)), whole);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be combined")]
+    fn tile_colliders_dont_consolidate() {
+        dirt_app(
+            DirtPlugin::single("Dirt", 1)
+                .with_consolidate(8)
+                .with_tile_colliders(8),
+        );
+    }
+
+    #[test]
+    fn tile_colliders_go_in_the_grid_without_entities() {
+        let mut app = dirt_app(DirtPlugin::single("Dirt", 1).with_tile_colliders(8));
+        add_block(&mut app, 3, 3);
+
+        let tiles = app.world().resource::<TileColliders>();
+        assert_eq!(tiles.iter_tiles().count(), 9);
+        let stxs = app
+            .world_mut()
+            .query::<&StaticTx>()
+            .iter(app.world())
+            .count();
+        assert_eq!(stxs, 0);
+    }
+
+    #[test]
+    fn tile_occluders_stay_hollow_and_merged() {
+        let mut app = dirt_app(DirtPlugin::single("Dirt", 1).with_tile_colliders(8));
+        add_block(&mut app, 3, 3);
+
+        // The middle is surrounded, so only the cells around it occlude, in fewer boxes than cells
+        let boxes = occluder_boxes(&mut app);
+        assert!(boxes.len() < 8);
+        let hollow = vec![
+            vec![true, true, true],
+            vec![true, false, true],
+            vec![true, true, true],
+        ];
+        assert_eq!(covered_cells(&boxes, 3, 3), hollow);
+
+        // Digging out the bottom middle opens up the middle
+        app.world_mut().trigger(RemoveDirt::new(Pos::new(8, 0)));
+        step(&mut app, 1);
+        let tiles = app.world().resource::<TileColliders>();
+        assert_eq!(tiles.iter_tiles().count(), 8);
+        let boxes = occluder_boxes(&mut app);
+        assert_eq!(
+            covered_cells(&boxes, 3, 3),
+            vec![
+                vec![true, false, true],
+                vec![true, true, true],
+                vec![true, true, true],
+            ]
+        );
+
+        // Filling it back in hollows the middle again
+        app.world_mut().trigger(AddDirt::new(Pos::new(8, 0), 1));
+        step(&mut app, 1);
+        let boxes = occluder_boxes(&mut app);
+        assert!(boxes.len() < 8);
+        assert_eq!(covered_cells(&boxes, 3, 3), hollow);
+    }
+}
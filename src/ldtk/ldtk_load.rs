@@ -3,7 +3,7 @@ use bevy_ecs_ldtk::{LdtkProjectHandle, LdtkWorldBundle, LevelSelection};
 
 use crate::{
     ldtk::ldtk_maint::update_level_rects,
-    prelude::{DynamicCamera, LdtkLevelRects, Pos},
+    prelude::{DynamicCamera, LdtkLevelRects, Teleport},
 };
 
 use super::{
@@ -86,7 +86,7 @@ fn update_load_ldtk(
     mut blockers: Query<(Entity, &mut BlockLdtkLoad)>,
     rects: Res<LdtkLevelRects>,
     level_selection: Res<LevelSelection>,
-    mut cam_pos: Query<&mut Pos, With<DynamicCamera>>,
+    cam: Query<Entity, With<DynamicCamera>>,
     // Need at least one frame to ensure level rects load in time so we can default to camera
     // going to the middle of the screen
     mut at_least_one_frame: Local<bool>,
@@ -105,10 +105,11 @@ fn update_load_ldtk(
             return;
         }
         if let LevelSelection::Iid(lid) = level_selection.into_inner() {
-            let mut cam_pos = cam_pos.single_mut().unwrap();
+            let cam_eid = cam.single().unwrap();
             if let Some(rect) = rects.get(&lid) {
-                let center = Pos::from(rect.center());
-                *cam_pos = center;
+                let center = rect.center();
+                // Same as placing it fresh, back at z 0
+                commands.trigger(Teleport::new(cam_eid, center.x, center.y).with_z(0));
             }
         };
         next_state.set(LdtkState::Loaded);
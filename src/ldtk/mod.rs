@@ -16,7 +16,8 @@ pub mod prelude {
         LdtkBundleEntity, LdtkBundleEntityPluginGeneric, LdtkEntity, LdtkEntityPluginGeneric,
     };
     pub use super::ldtk_int_cell::{
-        LdtkIntCellConsolidate, LdtkIntCellLayerer, LdtkIntCellValue, LdtkIntCellValuePluginGeneric,
+        AddLdtkIntCellGeneric, LdtkIntCellConsolidate, LdtkIntCellLayerer, LdtkIntCellValue,
+        LdtkIntCellValuePluginGeneric, LdtkSlope, RemoveLdtkIntCellGeneric,
     };
     pub use super::ldtk_load::{LdtkState, LoadLdtk, UnloadLdtk};
     pub use super::ldtk_maint::LdtkLevelRects;
@@ -5,7 +5,7 @@ use rand::{thread_rng, Rng};
 use crate::{
     fx,
     glue::Fx,
-    prelude::{FVec2, Layer, Pos, StaticRxKind, Terp, TerpMode},
+    prelude::{FVec2, Layer, PhysicsLayers, Pos, StaticRxKind, Terp, TerpMode},
 };
 
 #[derive(Clone)]
@@ -14,6 +14,7 @@ pub(super) struct ParticleMovement {
     pub(super) gravity: Option<Fx>,
     pub(super) drag: Option<Fx>,
     pub(super) collision: Option<StaticRxKind>,
+    pub(super) collision_layers: PhysicsLayers,
 }
 impl Default for ParticleMovement {
     fn default() -> Self {
@@ -22,6 +23,7 @@ impl Default for ParticleMovement {
             gravity: None,
             drag: None,
             collision: None,
+            collision_layers: default(),
         }
     }
 }
@@ -169,6 +171,10 @@ impl Particle {
         self.movement.collision = Some(collision);
         self
     }
+    pub fn with_collision_layers(mut self, layers: PhysicsLayers) -> Self {
+        self.movement.collision_layers = layers;
+        self
+    }
     pub fn with_size_constant<S: ToFixed>(mut self, size: S) -> Self {
         self.size = ParticleFxInner::Constant(fx!(size));
         self
@@ -134,7 +134,10 @@ fn bless_lifespans(
             particle.movement.initial_vel.y,
         ));
         if let Some(srx_kind) = particle.movement.collision {
-            comms.insert(StaticRx::single(srx_kind, HBox::new(size, size)));
+            comms.insert(
+                StaticRx::single(srx_kind, HBox::new(size, size))
+                    .with_layers(particle.movement.collision_layers),
+            );
         }
 
         comms.insert(lifespan);
@@ -193,10 +196,13 @@ fn update_particles(
             debug_assert!(srx.comps.len() == 1);
             if srx.comps[0].hbox.get_size().x != current_size.to_num::<u32>() {
                 commands.entity(eid).remove::<StaticRx>();
-                commands.entity(eid).insert(StaticRx::single(
-                    srx.comps[0].kind,
-                    HBox::new(current_size.to_num(), current_size.to_num()),
-                ));
+                commands.entity(eid).insert(
+                    StaticRx::single(
+                        srx.comps[0].kind,
+                        HBox::new(current_size.to_num(), current_size.to_num()),
+                    )
+                    .with_layers(srx.comps[0].layers),
+                );
             }
         }
         pos.z -= bullet_time.delta_secs() / 100;
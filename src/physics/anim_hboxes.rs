@@ -0,0 +1,103 @@
+//! Copies the hboxes that `AnimHBoxes` read from Aseprite slices onto the physics comps with the
+//! same marker. Lives here because only the physics side knows about the trigger kinds.
+
+use bevy::prelude::*;
+
+use crate::{
+    anim::AnimPreSet,
+    physics::{
+        layers::PhysicsLayers,
+        statics::{StaticRx, StaticTxHBoxes},
+        triggers::{TriggerKindTrait, TriggerRxGeneric, TriggerTxHBoxesGeneric},
+        PhysicsSet,
+    },
+    prelude::{AnimHBoxes, HashMap},
+};
+
+/// While a slice is hidden the comps it drives are taken off every layer.
+/// This remembers what they had (by comp index) so they get it back when the slice shows again.
+#[derive(Component, Clone, Debug, Default)]
+struct HiddenLayers {
+    srx: HashMap<usize, PhysicsLayers>,
+    stx: HashMap<usize, PhysicsLayers>,
+    trx: HashMap<usize, PhysicsLayers>,
+    ttx: HashMap<usize, PhysicsLayers>,
+}
+impl HiddenLayers {
+    fn is_empty(&self) -> bool {
+        self.srx.is_empty() && self.stx.is_empty() && self.trx.is_empty() && self.ttx.is_empty()
+    }
+}
+
+/// Swaps in the anim's hbox for every comp that has one with the same marker, hiding (or showing)
+/// the comps whose slice is hidden (or back)
+macro_rules! apply_to_comps {
+    ($anim_hboxes:expr, $comps:expr, $hidden:expr) => {
+        for (ix, comp) in $comps.iter_mut().enumerate() {
+            let marker = comp.hbox.get_marker();
+            if let Some(hbox) = $anim_hboxes.get_hbox(marker) {
+                comp.hbox = hbox.clone();
+                if let Some(layers) = $hidden.remove(&ix) {
+                    comp.layers = layers;
+                }
+            } else if $anim_hboxes.is_hidden(marker) && !$hidden.contains_key(&ix) {
+                $hidden.insert(ix, comp.layers);
+                comp.layers = PhysicsLayers::NONE;
+            }
+        }
+    };
+}
+
+fn apply_anim_hboxes<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
+    mut ents: Query<
+        (
+            Entity,
+            &AnimHBoxes,
+            Option<&mut StaticRx>,
+            Option<&mut TriggerRxGeneric<TriggerRxKind>>,
+            Option<&mut HiddenLayers>,
+        ),
+        Changed<AnimHBoxes>,
+    >,
+    mut stx_hboxes: StaticTxHBoxes,
+    mut ttx_hboxes: TriggerTxHBoxesGeneric<TriggerTxKind>,
+    mut commands: Commands,
+) {
+    for (eid, anim_hboxes, srx, trx, hidden) in &mut ents {
+        let had_hidden = hidden.is_some();
+        let mut new_hidden = HiddenLayers::default();
+        let hidden = match hidden {
+            Some(hidden) => hidden.into_inner(),
+            None => &mut new_hidden,
+        };
+        if let Some(mut srx) = srx {
+            apply_to_comps!(anim_hboxes, srx.comps, hidden.srx);
+        }
+        if let Some(mut trx) = trx {
+            apply_to_comps!(anim_hboxes, trx.comps, hidden.trx);
+        }
+        stx_hboxes.edit(eid, |stx| {
+            apply_to_comps!(anim_hboxes, stx.comps, hidden.stx)
+        });
+        ttx_hboxes.edit(eid, |ttx| {
+            apply_to_comps!(anim_hboxes, ttx.comps, hidden.ttx)
+        });
+        if !had_hidden && !new_hidden.is_empty() {
+            commands.entity(eid).insert(new_hidden);
+        }
+    }
+}
+
+pub(super) fn register_anim_hboxes<
+    TriggerRxKind: TriggerKindTrait,
+    TriggerTxKind: TriggerKindTrait,
+>(
+    app: &mut App,
+) {
+    app.add_systems(
+        Update,
+        apply_anim_hboxes::<TriggerRxKind, TriggerTxKind>
+            .after(AnimPreSet)
+            .before(PhysicsSet),
+    );
+}
@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+
+use crate::{
+    fx,
+    glue::{fvec::FVec2, Fx},
+    physics::{
+        hbox::HBox,
+        layers::PhysicsLayers,
+        pos::Pos,
+        prelude::{StaticRx, StaticRxKind, StaticTx, StaticTxKind},
+        tiles::TileColliders,
+    },
+    prelude::HashMap,
+};
+
+use super::spat_hash::{SpatHash, SpatHashStaticRx, SpatHashStaticTx, SpatKeys};
+
+/// Triggered on a StaticRx ctrl when a moving StaticTx pushes it into another solid
+#[derive(EntityEvent, Clone, Debug)]
+pub struct StaticCrush {
+    /// The rx ctrl that got crushed
+    pub entity: Entity,
+    /// The moving tx ctrl that did the pushing
+    pub tx_ctrl: Entity,
+    /// The tx ctrl it got pushed into
+    pub against_ctrl: Entity,
+}
+
+/// Where the `StaticRx`s are, for finding the ones near something without checking every rx.
+/// Built fresh by whatever system needs it, and kept up to date as that system moves rxs around.
+pub(super) struct RxIndex {
+    hash: SpatHash<SpatHashStaticRx>,
+    keys: HashMap<Entity, SpatKeys<SpatHashStaticRx>>,
+}
+impl RxIndex {
+    pub(super) fn new<'a>(rxs: impl Iterator<Item = (Entity, Pos, &'a StaticRx)>) -> Self {
+        let mut index = Self {
+            hash: SpatHash::new(default()),
+            keys: default(),
+        };
+        for (eid, pos, srx) in rxs {
+            index.update(eid, pos, srx);
+        }
+        index
+    }
+    /// Call after moving a rx so it can still be found
+    pub(super) fn update(&mut self, eid: Entity, pos: Pos, srx: &StaticRx) {
+        let hboxes = srx.comps.iter().map(|comp| comp.hbox.clone()).collect();
+        let keys = match self.keys.get(&eid) {
+            Some(old_keys) => self.hash.update(eid, old_keys, pos, hboxes),
+            None => self.hash.insert(eid, pos, hboxes),
+        };
+        self.keys.insert(eid, keys);
+    }
+    /// Every rx that might be overlapping (or touching) any of the thboxes, in entity order
+    pub(super) fn get_near<'a>(&self, thboxes: impl Iterator<Item = &'a HBox>) -> Vec<Entity> {
+        let (mut min, mut max) = (FVec2::new(Fx::MAX, Fx::MAX), FVec2::new(Fx::MIN, Fx::MIN));
+        for thbox in thboxes {
+            min = min.min(thbox.bottom_left());
+            max = max.max(thbox.top_right());
+        }
+        if min.x > max.x {
+            return vec![];
+        }
+        // Touching counts (riding), and something touching on a cell edge only has keys on its side
+        let pad = FVec2::ONE;
+        self.get_in_region(min - pad, max + pad)
+    }
+    /// Every rx that might be inside the region, in entity order
+    pub(super) fn get_in_region(&self, min: FVec2, max: FVec2) -> Vec<Entity> {
+        let mut eids = self
+            .hash
+            .get_eids(self.hash.get_region_keys(min, max))
+            .into_iter()
+            .collect::<Vec<_>>();
+        eids.sort();
+        eids
+    }
+}
+
+/// How a rx is attached to a moving tx
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Riding {
+    Top,
+    Side,
+}
+
+/// The (layers and) thboxes of a rx that should get carried/pushed around.
+/// Observe comps just go along for the ride.
+pub(super) fn carried_thboxes(srx: &StaticRx, pos: Pos) -> Vec<(PhysicsLayers, HBox)> {
+    srx.comps
+        .iter()
+        .filter(|comp| comp.kind != StaticRxKind::Observe)
+        .map(|comp| (comp.layers, comp.hbox.translated(pos.as_fvec2())))
+        .collect()
+}
+
+/// Figures out if (and how) a rx is riding a tx, given their translated hboxes.
+/// A tx that's about to `rise` also picks up things up to that far above it. Riders on a rising
+/// tx get bounced up by collision resolution, so they're usually hovering a bit when it next moves.
+pub(super) fn get_riding(
+    rx_thboxes: &[(PhysicsLayers, HBox)],
+    tx_comps: &[(StaticTxKind, PhysicsLayers, HBox)],
+    side_carry: bool,
+    rise: Fx,
+) -> Option<Riding> {
+    // Positions are exact after being pushed out, this just covers any weird rounding
+    let slop = fx!(1) / 64;
+    let mut result = None;
+    for (rx_layers, rx_thbox) in rx_thboxes {
+        for (tx_kind, tx_layers, tx_thbox) in tx_comps {
+            if !rx_layers.interacts_with(tx_layers) {
+                continue;
+            }
+            let x_overlap =
+                rx_thbox.min_x() < tx_thbox.max_x() && tx_thbox.min_x() < rx_thbox.max_x();
+            let y_overlap =
+                rx_thbox.min_y() < tx_thbox.max_y() && tx_thbox.min_y() < rx_thbox.max_y();
+            if x_overlap
+                && matches!(tx_kind, StaticTxKind::Solid | StaticTxKind::PassUp)
+                && rx_thbox.min_y() - tx_thbox.max_y() >= -slop
+                && rx_thbox.min_y() - tx_thbox.max_y() <= rise.max(Fx::ZERO) + slop
+            {
+                return Some(Riding::Top);
+            }
+            if side_carry
+                && *tx_kind == StaticTxKind::Solid
+                && y_overlap
+                && ((rx_thbox.max_x() - tx_thbox.min_x()).abs() <= slop
+                    || (rx_thbox.min_x() - tx_thbox.max_x()).abs() <= slop)
+            {
+                result = Some(Riding::Side);
+            }
+        }
+    }
+    result
+}
+
+/// Returns the first solid tx ctrl (that isn't `ignore`) that any of the given thboxes overlap with
+pub(super) fn first_solid_overlap(
+    thboxes: &[(PhysicsLayers, HBox)],
+    ignore: Entity,
+    pos_q: &Query<&mut Pos>,
+    stx_q: &Query<(Entity, &StaticTx)>,
+    spat_hash_stx: &SpatHash<SpatHashStaticTx>,
+    tiles: &TileColliders,
+) -> Option<Entity> {
+    let keys = spat_hash_stx.get_keys(
+        Pos::default(),
+        thboxes.iter().map(|(_, thbox)| thbox.clone()).collect(),
+    );
+    let mut candidate_eids = spat_hash_stx
+        .get_eids(keys)
+        .into_iter()
+        .filter(|eid| *eid != ignore)
+        .collect::<Vec<_>>();
+    // Sorted so crush events are deterministic
+    candidate_eids.sort();
+    let hit_eid = candidate_eids.into_iter().find(|eid| {
+        let Ok((_, stx)) = stx_q.get(*eid) else {
+            return false;
+        };
+        let Ok(pos) = pos_q.get(*eid) else {
+            return false;
+        };
+        stx.comps
+            .iter()
+            .filter(|comp| comp.kind == StaticTxKind::Solid)
+            .any(|comp| {
+                let tx_thbox = comp.hbox.translated(pos.as_fvec2());
+                thboxes.iter().any(|(layers, thbox)| {
+                    layers.interacts_with(&comp.layers) && thbox.overlaps_with(&tx_thbox)
+                })
+            })
+    });
+    hit_eid.or_else(|| {
+        let just_thboxes = thboxes
+            .iter()
+            .map(|(_, thbox)| thbox.clone())
+            .collect::<Vec<_>>();
+        tiles
+            .get_overlapping(&just_thboxes)
+            .into_iter()
+            .filter(|tile| tile.kind == StaticTxKind::Solid && tile.ctrl != ignore)
+            .find(|tile| {
+                thboxes.iter().any(|(layers, thbox)| {
+                    layers.interacts_with(&tile.layers) && thbox.overlaps_with(&tile.thbox)
+                })
+            })
+            .map(|tile| tile.ctrl)
+    })
+}
+
+/// Moves a rx by `delta`, at most `max_step` at a time, horizontally then vertically.
+/// Stops moving along an axis as soon as it would end up inside a solid (that isn't `ignore`).
+/// Returns how far it actually got.
+pub(super) fn move_rx_blocked(
+    eid: Entity,
+    srx: &StaticRx,
+    delta: FVec2,
+    ignore: Entity,
+    max_step: Fx,
+    pos_q: &mut Query<&mut Pos>,
+    stx_q: &Query<(Entity, &StaticTx)>,
+    spat_hash_stx: &SpatHash<SpatHashStaticTx>,
+    tiles: &TileColliders,
+) -> FVec2 {
+    let Ok(pos) = pos_q.get(eid) else {
+        return FVec2::ZERO;
+    };
+    let start_pos = *pos;
+    let mut scratch_pos = pos.clone();
+    for (amt, axis) in [(delta.x, FVec2::X), (delta.y, FVec2::Y)] {
+        let mut amt_moved = Fx::ZERO;
+        while amt_moved < amt.abs() {
+            let moving_this_step = max_step.min(amt.abs() - amt_moved);
+            let attempt = scratch_pos.clone() + axis * (amt.signum() * moving_this_step);
+            let thboxes = carried_thboxes(srx, attempt.clone());
+            if first_solid_overlap(&thboxes, ignore, pos_q, stx_q, spat_hash_stx, tiles).is_some() {
+                break;
+            }
+            scratch_pos = attempt;
+            amt_moved += moving_this_step;
+        }
+    }
+    if let Ok(mut pos) = pos_q.get_mut(eid) {
+        *pos = scratch_pos;
+    }
+    scratch_pos.as_fvec2() - start_pos.as_fvec2()
+}
+
+/// How far a rx needs to be pushed horizontally so that it's no longer inside a tx that just moved by `dx`.
+/// NOTE: Vertical movement is already handled by the normal collision resolution (which also
+///       gives riders the tx's vertical velocity), so we only need to worry about horizontal here.
+pub(super) fn get_push_hor(rx_thbox: &HBox, tx_thbox: &HBox, dx: Fx) -> Option<Fx> {
+    if !rx_thbox.overlaps_with(tx_thbox) {
+        return None;
+    }
+    if dx > Fx::ZERO {
+        Some(tx_thbox.max_x() - rx_thbox.min_x())
+    } else if dx < Fx::ZERO {
+        Some(tx_thbox.min_x() - rx_thbox.max_x())
+    } else {
+        None
+    }
+}
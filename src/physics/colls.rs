@@ -37,8 +37,13 @@ pub struct StaticCollRec {
     pub tx_kind: StaticTxKind,
     /// The marker of the hbox on the tx  triggering this collision
     pub tx_hbox: HBoxMarker,
+    /// Outward normal of the tx's surface where it was hit. Unlike `push`, follows slopes.
+    pub tx_normal: FVec2,
+    /// True when the tx is pass-through and the rx was ignoring it, so nothing was resolved.
+    /// (`push` is still what WOULD have happened)
+    pub pass_ignored: bool,
 }
-#[derive(Resource, Debug)]
+#[derive(Resource, Clone, Debug)]
 pub struct StaticColls {
     pub(crate) map: HashMap<CollKey, StaticCollRec>,
 }
@@ -83,7 +88,7 @@ pub struct TriggerCollRecGeneric<TriggerRxKind: TriggerKindTrait, TriggerTxKind:
     /// The marker of the hbox on the tx triggering this collision
     pub tx_hbox: HBoxMarker,
 }
-#[derive(Resource, Debug)]
+#[derive(Resource, Clone, Debug)]
 pub struct TriggerCollsGeneric<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> {
     pub(crate) map: HashMap<CollKey, TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>>,
 }
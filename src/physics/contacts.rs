@@ -0,0 +1,215 @@
+//! A per-entity summary of what each `StaticRx` is touching, so gameplay code doesn't have to dig
+//! through `StaticColls` and squint at push vectors to find out if something is grounded.
+
+use bevy::prelude::*;
+
+use crate::{
+    glue::{fvec::FVec2, Fx},
+    physics::{
+        colls::StaticColls,
+        statics::{StaticRx, StaticRxKind, StaticTxKind},
+    },
+};
+
+/// A side of the rx
+#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
+pub enum ContactSide {
+    Bottom,
+    Top,
+    Left,
+    Right,
+}
+impl ContactSide {
+    /// Which side a push came from. Slopes count as whichever way they push the most.
+    fn from_push(push: FVec2) -> Option<Self> {
+        if push.y.abs() >= push.x.abs() {
+            if push.y > Fx::ZERO {
+                Some(Self::Bottom)
+            } else if push.y < Fx::ZERO {
+                Some(Self::Top)
+            } else {
+                None
+            }
+        } else if push.x > Fx::ZERO {
+            Some(Self::Left)
+        } else {
+            Some(Self::Right)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Contact {
+    /// Entity of the control associated with the tx being touched
+    pub tx_ctrl: Entity,
+    /// The kind of the tx being touched
+    pub tx_kind: StaticTxKind,
+    /// Normal of the surface being touched (pointing out of it, towards us)
+    pub normal: FVec2,
+    /// How many physics steps in a row we've been touching this tx on this side. Starts at 1.
+    pub frames: u32,
+}
+
+/// What a `StaticRx` touched during the last physics step. Kept up to date on every rx.
+/// NOTE: Only real collisions count. Observe comps and ignored pass-throughs don't show up here.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Contacts {
+    bottom: Option<Contact>,
+    top: Option<Contact>,
+    left: Option<Contact>,
+    right: Option<Contact>,
+}
+impl Contacts {
+    pub fn get(&self, side: ContactSide) -> Option<&Contact> {
+        match side {
+            ContactSide::Bottom => self.bottom.as_ref(),
+            ContactSide::Top => self.top.as_ref(),
+            ContactSide::Left => self.left.as_ref(),
+            ContactSide::Right => self.right.as_ref(),
+        }
+    }
+    fn get_mut(&mut self, side: ContactSide) -> &mut Option<Contact> {
+        match side {
+            ContactSide::Bottom => &mut self.bottom,
+            ContactSide::Top => &mut self.top,
+            ContactSide::Left => &mut self.left,
+            ContactSide::Right => &mut self.right,
+        }
+    }
+    pub fn is_touching(&self, side: ContactSide) -> bool {
+        self.get(side).is_some()
+    }
+    pub fn is_grounded(&self) -> bool {
+        self.bottom.is_some()
+    }
+    pub fn ground(&self) -> Option<&Contact> {
+        self.bottom.as_ref()
+    }
+    /// Straight up on flat ground, tilted on slopes
+    pub fn ground_normal(&self) -> Option<FVec2> {
+        self.bottom.as_ref().map(|contact| contact.normal)
+    }
+    /// Which way the wall we're touching is (-1 left, 1 right). Prefers left if touching both.
+    pub fn wall_dir(&self) -> Option<Fx> {
+        if self.left.is_some() {
+            Some(-Fx::ONE)
+        } else if self.right.is_some() {
+            Some(Fx::ONE)
+        } else {
+            None
+        }
+    }
+}
+
+pub(super) fn update_contacts(
+    static_colls: Res<StaticColls>,
+    mut srx_q: Query<(&StaticRx, &mut Contacts)>,
+) {
+    for (srx, mut contacts) in &mut srx_q {
+        let mut new_contacts = Contacts::default();
+        // The biggest push on each side wins, ties broken by entity so it's deterministic
+        let mut biggest = [Fx::ZERO; 4];
+        for coll in static_colls
+            .iter_refs(&srx.coll_keys)
+            .filter(|coll| coll.rx_kind != StaticRxKind::Observe && !coll.pass_ignored)
+        {
+            let Some(side) = ContactSide::from_push(coll.push) else {
+                continue;
+            };
+            let size = coll.push.x.abs().max(coll.push.y.abs());
+            let slot = new_contacts.get_mut(side);
+            let ix = side as usize;
+            let better = match slot {
+                Some(existing) => {
+                    size > biggest[ix] || (size == biggest[ix] && coll.tx_ctrl < existing.tx_ctrl)
+                }
+                None => true,
+            };
+            if better {
+                biggest[ix] = size;
+                *slot = Some(Contact {
+                    tx_ctrl: coll.tx_ctrl,
+                    tx_kind: coll.tx_kind,
+                    normal: coll.tx_normal,
+                    frames: 1,
+                });
+            }
+        }
+        // Keep counting if we're still touching the same thing on the same side
+        for side in [
+            ContactSide::Bottom,
+            ContactSide::Top,
+            ContactSide::Left,
+            ContactSide::Right,
+        ] {
+            let (Some(new), Some(old)) = (new_contacts.get_mut(side).as_mut(), contacts.get(side))
+            else {
+                continue;
+            };
+            if new.tx_ctrl == old.tx_ctrl {
+                new.frames = old.frames.saturating_add(1);
+            }
+        }
+        *contacts = new_contacts;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fx,
+        physics::{
+            dyno::DynoBody,
+            hbox::{HBox, HBoxCorner},
+            pos::Pos,
+            statics::StaticTx,
+            test_utils::{step, test_app, TestSettings},
+        },
+    };
+
+    #[test]
+    fn ground_normal_follows_slopes() {
+        let mut app = test_app(TestSettings::default().with_gravity(512));
+        // Slope goes from (-8, 8) down to (8, -8)
+        app.world_mut().spawn((
+            Pos::new(0, 0),
+            StaticTx::single(
+                StaticTxKind::Solid,
+                HBox::ramp_45(16, HBoxCorner::BottomLeft),
+            ),
+        ));
+        app.world_mut().spawn((
+            Pos::new(100, 0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(16, 16)),
+        ));
+        let on_slope = app
+            .world_mut()
+            .spawn((
+                Pos::new(0, 10),
+                DynoBody::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id();
+        let on_flat = app
+            .world_mut()
+            .spawn((
+                Pos::new(100, 20),
+                DynoBody::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id();
+        step(&mut app, 30);
+
+        let ground_normal = |eid: Entity| {
+            app.world()
+                .get::<Contacts>(eid)
+                .unwrap()
+                .ground_normal()
+                .expect("Not grounded")
+        };
+        let slope_normal = ground_normal(on_slope);
+        assert!((slope_normal - FVec2::new(1, 1).normalize_or_zero()).length() < fx!(0.001));
+        assert_eq!(ground_normal(on_flat), FVec2::new(0, 1));
+    }
+}
@@ -0,0 +1,423 @@
+//! A reusable platformer controller, so games don't have to hand-roll coyote time, jump
+//! buffering, jump cuts and wall jumps on top of `StaticRx` + `Dyno` every time.
+//! It only handles moving. Gravity comes from `DynoBody`, and anims/etc. can read the flags.
+
+use bevy::prelude::*;
+use fixed::traits::ToFixed;
+
+use crate::{
+    fx,
+    glue::{fvec::FVec2, Fx},
+    input::prelude::{Butt, Input, Stick},
+    physics::{contacts::Contacts, dyno::Dyno, timestep::PhysicsDelta},
+};
+
+/// How far the stick has to be pushed before it counts as running
+const STICK_DEADZONE: f32 = 0.2;
+
+#[derive(Component, Clone, Debug)]
+#[require(Dyno)]
+pub struct PlatformerController {
+    /// Top horizontal speed from running
+    pub run_speed: Fx,
+    /// Horizontal acceleration (pixels/sec²) when running on the ground
+    pub run_accel: Fx,
+    /// Horizontal acceleration (pixels/sec²) when steering in the air
+    pub air_accel: Fx,
+    /// Horizontal deceleration (pixels/sec²) when there's no input on the ground
+    pub ground_decel: Fx,
+    /// Horizontal deceleration (pixels/sec²) when there's no input in the air
+    pub air_decel: Fx,
+    /// Upward speed given by a jump
+    pub jump_speed: Fx,
+    /// What upward speed gets multiplied by when jump is let go early. One means no jump cut.
+    pub jump_cut: Fx,
+    /// How many physics steps after leaving the ground you can still jump
+    pub coyote_frames: u32,
+    /// How many physics steps early a jump press still counts once you land
+    pub buffer_frames: u32,
+    /// Fastest you can fall while pushing into a wall. `None` turns off wall sliding AND wall jumping.
+    pub wall_slide_speed: Option<Fx>,
+    /// Velocity given by a wall jump. X is away from the wall.
+    pub wall_jump_vel: FVec2,
+    /// Which stick steers
+    pub stick: Stick,
+    /// Which button jumps
+    pub jump_butt: Butt,
+    grounded: bool,
+    wall_dir: Option<Fx>,
+    coyote_left: u32,
+    buffer_left: u32,
+    jump_was_held: bool,
+    cuttable: bool,
+    just_jumped: bool,
+    just_landed: bool,
+}
+impl Default for PlatformerController {
+    fn default() -> Self {
+        Self {
+            run_speed: fx!(100),
+            run_accel: fx!(600),
+            air_accel: fx!(300),
+            ground_decel: fx!(800),
+            air_decel: fx!(100),
+            jump_speed: fx!(100),
+            jump_cut: fx!(0.5),
+            coyote_frames: 6,
+            buffer_frames: 6,
+            wall_slide_speed: None,
+            wall_jump_vel: FVec2::new(80, 100),
+            stick: Stick::Left,
+            jump_butt: Butt::South,
+            grounded: false,
+            wall_dir: None,
+            coyote_left: 0,
+            buffer_left: 0,
+            jump_was_held: false,
+            cuttable: false,
+            just_jumped: false,
+            just_landed: false,
+        }
+    }
+}
+impl PlatformerController {
+    pub fn with_run<S: ToFixed, G: ToFixed, A: ToFixed>(
+        mut self,
+        speed: S,
+        ground_accel: G,
+        air_accel: A,
+    ) -> Self {
+        self.run_speed = fx!(speed);
+        self.run_accel = fx!(ground_accel);
+        self.air_accel = fx!(air_accel);
+        self
+    }
+    pub fn with_decel<G: ToFixed, A: ToFixed>(mut self, ground: G, air: A) -> Self {
+        self.ground_decel = fx!(ground);
+        self.air_decel = fx!(air);
+        self
+    }
+    pub fn with_jump<S: ToFixed, C: ToFixed>(mut self, speed: S, cut: C) -> Self {
+        self.jump_speed = fx!(speed);
+        self.jump_cut = fx!(cut);
+        self
+    }
+    pub fn with_coyote_frames(mut self, frames: u32) -> Self {
+        self.coyote_frames = frames;
+        self
+    }
+    pub fn with_buffer_frames(mut self, frames: u32) -> Self {
+        self.buffer_frames = frames;
+        self
+    }
+    pub fn with_wall<S: ToFixed>(mut self, slide_speed: S, jump_vel: FVec2) -> Self {
+        self.wall_slide_speed = Some(fx!(slide_speed));
+        self.wall_jump_vel = jump_vel;
+        self
+    }
+    pub fn with_bindings(mut self, stick: Stick, jump_butt: Butt) -> Self {
+        self.stick = stick;
+        self.jump_butt = jump_butt;
+        self
+    }
+
+    /// Standing on something (as of the last physics step)
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+    /// Which way the wall we're pushing into is (-1 left, 1 right), if we're in the air against one
+    pub fn get_wall_dir(&self) -> Option<Fx> {
+        self.wall_dir
+    }
+    /// In the air and pushing into a wall, with wall sliding turned on
+    pub fn is_wall_sliding(&self) -> bool {
+        self.wall_dir.is_some() && self.wall_slide_speed.is_some()
+    }
+    /// Jumped (or wall jumped) during the last physics step
+    pub fn just_jumped(&self) -> bool {
+        self.just_jumped
+    }
+    /// Landed during the last physics step
+    pub fn just_landed(&self) -> bool {
+        self.just_landed
+    }
+}
+
+/// Moves `from` towards `to` by at most `amt`
+fn approach(from: Fx, to: Fx, amt: Fx) -> Fx {
+    if from < to {
+        (from + amt).min(to)
+    } else {
+        (from - amt).max(to)
+    }
+}
+
+pub(super) fn update_platformer_controllers(
+    physics_delta: Res<PhysicsDelta>,
+    input: Res<Input>,
+    mut ctrl_q: Query<(&mut PlatformerController, &mut Dyno, Option<&Contacts>)>,
+) {
+    let dt = physics_delta.delta_secs();
+    for (mut ctrl, mut dyno, contacts) in &mut ctrl_q {
+        // Contacts from the last step
+        let grounded = contacts.is_some_and(|contacts| contacts.is_grounded());
+        ctrl.just_landed = grounded && !ctrl.grounded;
+        ctrl.just_jumped = false;
+        ctrl.grounded = grounded;
+        ctrl.wall_dir = contacts
+            .and_then(|contacts| contacts.wall_dir())
+            .filter(|_| !grounded);
+        if grounded {
+            ctrl.coyote_left = ctrl.coyote_frames;
+            ctrl.cuttable = false;
+        } else {
+            ctrl.coyote_left = ctrl.coyote_left.saturating_sub(1);
+        }
+
+        // Input. Edges are found here (not with `just_pressed`) so it works with any number of steps per frame.
+        let stick_x = input.sticks.dir(ctrl.stick).x;
+        let intent = if stick_x > STICK_DEADZONE {
+            Fx::ONE
+        } else if stick_x < -STICK_DEADZONE {
+            -Fx::ONE
+        } else {
+            Fx::ZERO
+        };
+        let jump_held = input.butts.pressed(ctrl.jump_butt);
+        if jump_held && !ctrl.jump_was_held {
+            ctrl.buffer_left = ctrl.buffer_frames + 1;
+        }
+        ctrl.buffer_left = ctrl.buffer_left.saturating_sub(1);
+
+        // Running
+        let mut vel = dyno.vel;
+        let target = intent * ctrl.run_speed;
+        let rate = match (intent == Fx::ZERO, grounded) {
+            (false, true) => ctrl.run_accel,
+            (false, false) => ctrl.air_accel,
+            (true, true) => ctrl.ground_decel,
+            (true, false) => ctrl.air_decel,
+        };
+        vel.x = approach(vel.x, target, rate * dt);
+
+        // Wall sliding
+        if let (Some(wall_dir), Some(slide_speed)) = (ctrl.wall_dir, ctrl.wall_slide_speed) {
+            if intent == wall_dir {
+                vel.y = vel.y.max(-slide_speed);
+            }
+        }
+
+        // Jumping
+        if ctrl.buffer_left > 0 {
+            if grounded || ctrl.coyote_left > 0 {
+                vel.y = ctrl.jump_speed;
+                ctrl.just_jumped = true;
+            } else if let (Some(wall_dir), Some(_)) = (ctrl.wall_dir, ctrl.wall_slide_speed) {
+                vel = FVec2::new(-wall_dir * ctrl.wall_jump_vel.x, ctrl.wall_jump_vel.y);
+                ctrl.just_jumped = true;
+            }
+            if ctrl.just_jumped {
+                ctrl.buffer_left = 0;
+                ctrl.coyote_left = 0;
+                ctrl.cuttable = true;
+            }
+        }
+        if ctrl.cuttable && !jump_held && vel.y > Fx::ZERO {
+            vel.y *= ctrl.jump_cut;
+            ctrl.cuttable = false;
+        }
+        if vel.y <= Fx::ZERO {
+            ctrl.cuttable = false;
+        }
+
+        ctrl.jump_was_held = jump_held;
+        dyno.vel = vel;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{
+        dyno::DynoBody,
+        hbox::HBox,
+        pos::Pos,
+        statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind},
+        test_utils::{step, test_app, TestSettings},
+    };
+
+    fn controller_app() -> App {
+        test_app(TestSettings::default().with_gravity(512))
+    }
+
+    fn spawn_floor(app: &mut App, x: i32) -> Entity {
+        // Top at y = 0
+        app.world_mut()
+            .spawn((
+                Pos::new(x, -8),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(32, 16)),
+            ))
+            .id()
+    }
+
+    fn spawn_player(app: &mut App, x: i32, y: i32, ctrl: PlatformerController) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(x, y),
+                DynoBody::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+                ctrl,
+            ))
+            .id()
+    }
+
+    fn hold(app: &mut App, butt: Butt, pressed: bool) {
+        app.world_mut()
+            .resource_mut::<Input>()
+            .set_pressed(butt, pressed);
+    }
+
+    fn ctrl_of(app: &App, eid: Entity) -> &PlatformerController {
+        app.world().get::<PlatformerController>(eid).unwrap()
+    }
+
+    fn vel_of(app: &App, eid: Entity) -> FVec2 {
+        app.world().get::<Dyno>(eid).unwrap().vel
+    }
+
+    #[test]
+    fn coyote_frames_let_you_jump_just_after_leaving_the_ground() {
+        let mut app = controller_app();
+        let floors = [spawn_floor(&mut app, 0), spawn_floor(&mut app, 100)];
+        let early = spawn_player(
+            &mut app,
+            0,
+            2,
+            PlatformerController::default().with_bindings(Stick::Left, Butt::South),
+        );
+        let late = spawn_player(
+            &mut app,
+            100,
+            2,
+            PlatformerController::default().with_bindings(Stick::Left, Butt::East),
+        );
+        step(&mut app, 5);
+        assert!(ctrl_of(&app, early).is_grounded());
+
+        // The ground goes away, and they start to fall
+        for floor in floors {
+            app.world_mut().despawn(floor);
+        }
+        step(&mut app, 3);
+        assert!(!ctrl_of(&app, early).is_grounded());
+        assert!(vel_of(&app, early).y < Fx::ZERO);
+        hold(&mut app, Butt::South, true);
+        step(&mut app, 1);
+        assert!(ctrl_of(&app, early).just_jumped());
+        assert_eq!(vel_of(&app, early).y, fx!(100));
+
+        // Six frames of coyote time is long gone
+        step(&mut app, 6);
+        hold(&mut app, Butt::East, true);
+        step(&mut app, 1);
+        assert!(!ctrl_of(&app, late).just_jumped());
+        assert!(vel_of(&app, late).y < Fx::ZERO);
+    }
+
+    #[test]
+    fn jumps_pressed_just_before_landing_still_count() {
+        let mut app = controller_app();
+        spawn_floor(&mut app, 0);
+        spawn_floor(&mut app, 100);
+        let buffered = spawn_player(
+            &mut app,
+            0,
+            40,
+            PlatformerController::default().with_bindings(Stick::Left, Butt::South),
+        );
+        let too_early = spawn_player(
+            &mut app,
+            100,
+            40,
+            PlatformerController::default().with_bindings(Stick::Left, Butt::East),
+        );
+        // Pressed (and held) long before landing, and falling about three pixels a step at the end
+        hold(&mut app, Butt::East, true);
+        let y_of = |app: &App, eid: Entity| app.world().get::<Pos>(eid).unwrap().y;
+        while y_of(&app, buffered) > fx!(8) {
+            step(&mut app, 1);
+        }
+        hold(&mut app, Butt::South, true);
+
+        let mut jumped = (false, false);
+        for _ in 0..20 {
+            step(&mut app, 1);
+            jumped.0 |= ctrl_of(&app, buffered).just_jumped();
+            jumped.1 |= ctrl_of(&app, too_early).just_jumped();
+        }
+        assert_eq!(jumped, (true, false));
+        assert!(ctrl_of(&app, too_early).is_grounded());
+    }
+
+    #[test]
+    fn letting_go_of_jump_early_cuts_it_short() {
+        let mut app = controller_app();
+        spawn_floor(&mut app, 0);
+        spawn_floor(&mut app, 100);
+        let held = spawn_player(
+            &mut app,
+            0,
+            2,
+            PlatformerController::default().with_bindings(Stick::Left, Butt::South),
+        );
+        let cut = spawn_player(
+            &mut app,
+            100,
+            2,
+            PlatformerController::default().with_bindings(Stick::Left, Butt::East),
+        );
+        step(&mut app, 5);
+        hold(&mut app, Butt::South, true);
+        hold(&mut app, Butt::East, true);
+        step(&mut app, 3);
+        let before = vel_of(&app, cut).y;
+        hold(&mut app, Butt::East, false);
+        step(&mut app, 1);
+        // Gravity first, then halved
+        assert_eq!(vel_of(&app, cut).y, (before - fx!(512) / 64) / 2);
+        assert_eq!(vel_of(&app, held).y, before - fx!(512) / 64);
+
+        // Only cut once, after that it's just gravity
+        let after_cut = vel_of(&app, cut).y;
+        step(&mut app, 1);
+        assert_eq!(vel_of(&app, cut).y, after_cut - fx!(512) / 64);
+    }
+
+    #[test]
+    fn wall_slides_and_wall_jumps() {
+        let mut app = controller_app();
+        // Left face at x = 2
+        app.world_mut().spawn((
+            Pos::new(10, 0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(16, 400)),
+        ));
+        let player = spawn_player(
+            &mut app,
+            0,
+            100,
+            PlatformerController::default().with_wall(20, FVec2::new(80, 100)),
+        );
+        app.world_mut()
+            .resource_mut::<Input>()
+            .set_stick(Stick::Left, Vec2::X);
+        step(&mut app, 30);
+        assert!(ctrl_of(&app, player).is_wall_sliding());
+        assert_eq!(ctrl_of(&app, player).get_wall_dir(), Some(Fx::ONE));
+        assert_eq!(vel_of(&app, player).y, fx!(-20));
+
+        hold(&mut app, Butt::South, true);
+        step(&mut app, 1);
+        assert!(ctrl_of(&app, player).just_jumped());
+        assert_eq!(vel_of(&app, player), FVec2::new(-80, 100));
+    }
+}
@@ -1,8 +1,10 @@
 use bevy::{input::common_conditions::input_toggle_active, prelude::*};
 
 use super::{
+    hbox::HBoxShape,
     pos::Pos,
     prelude::{StaticRx, StaticTx, TriggerKindTrait, TriggerRxGeneric, TriggerTxGeneric},
+    tiles::TileColliders,
     PhysicsSet,
 };
 
@@ -11,6 +13,7 @@ fn draw_hitboxes<TriggerRx: TriggerKindTrait, TriggerTx: TriggerKindTrait>(
     stx_q: Query<(&Pos, &StaticTx)>,
     trx_q: Query<(&Pos, &TriggerRxGeneric<TriggerRx>)>,
     ttx_q: Query<(&Pos, &TriggerTxGeneric<TriggerTx>)>,
+    tiles: Res<TileColliders>,
     mut gz: Gizmos,
 ) {
     for hbox in srx_q
@@ -20,12 +23,48 @@ fn draw_hitboxes<TriggerRx: TriggerKindTrait, TriggerTx: TriggerKindTrait>(
         .chain(trx_q.iter().map(|pair| pair.1.get_thboxes(*pair.0)))
         .chain(ttx_q.iter().map(|pair| pair.1.get_thboxes(*pair.0)))
         .flat_map(|v| v)
+        .chain(tiles.iter_tiles().map(|tile| tile.thbox))
     {
-        gz.rect_2d(
-            Isometry2d::from_translation(hbox.get_offset().as_vec2()),
-            hbox.get_size().as_vec2(),
-            Color::WHITE,
-        );
+        match hbox.get_shape() {
+            HBoxShape::Rect => {
+                gz.rect_2d(
+                    Isometry2d::from_translation(hbox.get_offset().as_vec2()),
+                    hbox.get_size().as_vec2(),
+                    Color::WHITE,
+                );
+            }
+            HBoxShape::Circle => {
+                let size = hbox.get_size().as_vec2();
+                gz.circle_2d(
+                    Isometry2d::from_translation(hbox.get_offset().as_vec2()),
+                    size.x.min(size.y) / 2.0,
+                    Color::WHITE,
+                );
+            }
+            HBoxShape::Capsule => {
+                let size = hbox.get_size().as_vec2();
+                let radius = size.x.min(size.y) / 2.0;
+                gz.primitive_2d(
+                    &Capsule2d::new(radius, size.x.max(size.y) - radius * 2.0),
+                    Isometry2d::new(
+                        hbox.get_offset().as_vec2(),
+                        if size.x > size.y {
+                            Rot2::degrees(90.0)
+                        } else {
+                            Rot2::IDENTITY
+                        },
+                    ),
+                    Color::WHITE,
+                );
+            }
+            HBoxShape::RightTri(_) => {
+                let vertices = hbox.vertices();
+                gz.linestrip_2d(
+                    vertices.iter().chain(vertices.first()).map(|v| v.as_vec2()),
+                    Color::WHITE,
+                );
+            }
+        }
     }
 }
 
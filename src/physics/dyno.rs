@@ -1,6 +1,17 @@
 use bevy::prelude::*;
+use fixed::traits::ToFixed;
 
-use crate::{glue::Fx, prelude::FVec2};
+use crate::{
+    fx,
+    glue::Fx,
+    physics::{
+        colls::StaticColls,
+        plugin::PhysicsDefaults,
+        statics::{StaticRx, StaticRxKind},
+        timestep::PhysicsDelta,
+    },
+    prelude::FVec2,
+};
 
 #[derive(Component, Clone, Debug, Default)]
 #[require(crate::physics::pos::Pos)]
@@ -14,3 +25,107 @@ impl Dyno {
         }
     }
 }
+
+/// Opt-in for fast movers (bullets, dashes, etc.).
+/// Instead of inching along, sweeps its hboxes over the whole displacement and stops at the
+/// first thing it would hit, so it can't tunnel through thin statics or skip triggers.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+pub struct ContinuousCollision;
+
+/// Opt-in per-body parameters. Everything here gets applied at the start of each physics step,
+/// scaled by `BulletTime`, so games don't have to re-implement gravity/friction/etc.
+#[derive(Component, Clone, Debug)]
+#[require(Dyno)]
+pub struct DynoBody {
+    /// Multiplies the gravity from `PhysicsSettingsGeneric`
+    pub gravity_scale: Fx,
+    /// Speed limit along each axis (not the length of the velocity)
+    pub max_speed: FVec2,
+    /// Fraction of velocity lost per second
+    pub drag: Fx,
+    /// Horizontal deceleration (pixels/sec²) while standing on something
+    pub ground_friction: Fx,
+    /// Horizontal deceleration (pixels/sec²) while not standing on anything
+    pub air_friction: Fx,
+    /// Accumulated forces, applied over the next step and then cleared
+    force: FVec2,
+    /// Accumulated impulses, applied at the start of the next step and then cleared
+    impulse: FVec2,
+}
+impl Default for DynoBody {
+    fn default() -> Self {
+        Self {
+            gravity_scale: Fx::ONE,
+            max_speed: FVec2::new(Fx::MAX, Fx::MAX),
+            drag: Fx::ZERO,
+            ground_friction: Fx::ZERO,
+            air_friction: Fx::ZERO,
+            force: FVec2::ZERO,
+            impulse: FVec2::ZERO,
+        }
+    }
+}
+impl DynoBody {
+    pub fn with_gravity_scale<S: ToFixed>(mut self, gravity_scale: S) -> Self {
+        self.gravity_scale = fx!(gravity_scale);
+        self
+    }
+    pub fn with_max_speed<X: ToFixed, Y: ToFixed>(mut self, x: X, y: Y) -> Self {
+        self.max_speed = FVec2::new(x, y);
+        self
+    }
+    pub fn with_drag<D: ToFixed>(mut self, drag: D) -> Self {
+        self.drag = fx!(drag);
+        self
+    }
+    pub fn with_friction<G: ToFixed, A: ToFixed>(mut self, ground: G, air: A) -> Self {
+        self.ground_friction = fx!(ground);
+        self.air_friction = fx!(air);
+        self
+    }
+    /// Pushes continuously. Call every frame you want it applied (like holding a jetpack).
+    pub fn add_force(&mut self, force: FVec2) {
+        self.force += force;
+    }
+    /// Instantly changes velocity (like a jump or a hit)
+    pub fn add_impulse(&mut self, impulse: FVec2) {
+        self.impulse += impulse;
+    }
+}
+
+/// Whether this rx was standing on something at the end of the last step
+fn is_grounded(srx: &StaticRx, static_colls: &StaticColls) -> bool {
+    static_colls.iter_refs(&srx.coll_keys).any(|coll| {
+        coll.push.y > Fx::ZERO && coll.rx_kind != StaticRxKind::Observe && !coll.pass_ignored
+    })
+}
+
+pub(super) fn apply_dyno_bodies(
+    physics_delta: Res<PhysicsDelta>,
+    physics_defaults: Res<PhysicsDefaults>,
+    static_colls: Res<StaticColls>,
+    mut bodies_q: Query<(&mut Dyno, &mut DynoBody, Option<&StaticRx>)>,
+) {
+    let dt = physics_delta.delta_secs();
+    for (mut dyno, mut body, srx) in &mut bodies_q {
+        let mut vel = dyno.vel + body.impulse;
+        vel += body.force * dt;
+        vel.y -= physics_defaults.gravity * body.gravity_scale * dt;
+        if body.drag != Fx::ZERO {
+            vel = vel * (Fx::ONE - body.drag * dt).max(Fx::ZERO);
+        }
+        let grounded = srx.is_some_and(|srx| is_grounded(srx, &static_colls));
+        let friction = if grounded {
+            body.ground_friction
+        } else {
+            body.air_friction
+        };
+        // Friction only ever slows down, it never flips direction
+        vel.x = vel.x.signum() * (vel.x.abs() - friction * dt).max(Fx::ZERO);
+        vel.x = vel.x.clamp(-body.max_speed.x, body.max_speed.x);
+        vel.y = vel.y.clamp(-body.max_speed.y, body.max_speed.y);
+        dyno.vel = vel;
+        body.force = FVec2::ZERO;
+        body.impulse = FVec2::ZERO;
+    }
+}
@@ -8,6 +8,38 @@ use crate::{
 
 pub type HBoxMarker = u32;
 
+/// A corner of an hbox
+#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
+pub enum HBoxCorner {
+    BottomLeft,
+    BottomRight,
+    TopLeft,
+    TopRight,
+}
+impl HBoxCorner {
+    fn is_left(&self) -> bool {
+        matches!(self, Self::BottomLeft | Self::TopLeft)
+    }
+    fn is_bottom(&self) -> bool {
+        matches!(self, Self::BottomLeft | Self::BottomRight)
+    }
+}
+
+/// What part of its bounds an hbox actually fills
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub enum HBoxShape {
+    #[default]
+    Rect,
+    /// Half of the box, cut along the diagonal, with the right angle in the given corner.
+    /// Floor ramps have their right angle at the bottom, ceiling ramps at the top.
+    RightTri(HBoxCorner),
+    /// The biggest circle that fits in the box, centered
+    Circle,
+    /// The box with its short ends fully rounded off (a stadium). Tall boxes make upright capsules.
+    /// NOTE: Circles and capsules are treated as their whole box when colliding with triangles.
+    Capsule,
+}
+
 /// HBOX?????
 #[derive(Clone, Debug)]
 pub struct HBox {
@@ -15,6 +47,7 @@ pub struct HBox {
     size: UVec2,
     half_size: FVec2,
     marker: HBoxMarker,
+    shape: HBoxShape,
 }
 impl HBox {
     pub fn new(w: u32, h: u32) -> Self {
@@ -23,8 +56,33 @@ impl HBox {
             size: UVec2::new(w, h),
             half_size: FVec2::new(fx!(w as i32) / fx!(2), fx!(h as i32) / fx!(2)),
             marker: default(),
+            shape: default(),
         }
     }
+    /// A right triangle with legs `w` and `h`, and the right angle in `corner`
+    pub fn right_tri(w: u32, h: u32, corner: HBoxCorner) -> Self {
+        Self::new(w, h).with_shape(HBoxShape::RightTri(corner))
+    }
+    /// A circle with radius `r`
+    pub fn circle(r: u32) -> Self {
+        Self::new(r * 2, r * 2).with_shape(HBoxShape::Circle)
+    }
+    /// A capsule that fills a `w` by `h` box, rounded along the shorter side
+    pub fn capsule(w: u32, h: u32) -> Self {
+        Self::new(w, h).with_shape(HBoxShape::Capsule)
+    }
+    /// A 45° ramp
+    pub fn ramp_45(size: u32, corner: HBoxCorner) -> Self {
+        Self::right_tri(size, size, corner)
+    }
+    /// A 2:1 ramp (the classic "22.5°" ramp, really more like 26.6°)
+    pub fn ramp_22(h: u32, corner: HBoxCorner) -> Self {
+        Self::right_tri(h * 2, h, corner)
+    }
+    pub fn with_shape(mut self, shape: HBoxShape) -> Self {
+        self.shape = shape;
+        self
+    }
     pub fn with_offset<X: ToFixed, Y: ToFixed>(mut self, x: X, y: Y) -> Self {
         self.offset.x = fx!(x);
         self.offset.y = fx!(y);
@@ -49,6 +107,7 @@ impl HBox {
             size: self.size,
             half_size: self.half_size,
             marker: self.marker,
+            shape: self.shape,
         }
     }
     pub fn min_x(&self) -> Fx {
@@ -72,6 +131,9 @@ impl HBox {
     pub fn get_marker(&self) -> HBoxMarker {
         self.marker
     }
+    pub fn get_shape(&self) -> HBoxShape {
+        self.shape
+    }
     pub fn bottom_left(&self) -> FVec2 {
         self.offset - self.half_size
     }
@@ -84,6 +146,96 @@ impl HBox {
     pub fn top_right(&self) -> FVec2 {
         self.offset + self.half_size
     }
+    /// The corners of the shape, counter-clockwise. Round shapes give the corners of their box.
+    pub fn vertices(&self) -> Vec<FVec2> {
+        match self.shape {
+            HBoxShape::Rect | HBoxShape::Circle | HBoxShape::Capsule => vec![
+                self.bottom_left(),
+                self.bottom_right(),
+                self.top_right(),
+                self.top_left(),
+            ],
+            HBoxShape::RightTri(HBoxCorner::BottomLeft) => {
+                vec![self.bottom_left(), self.bottom_right(), self.top_left()]
+            }
+            HBoxShape::RightTri(HBoxCorner::BottomRight) => {
+                vec![self.bottom_left(), self.bottom_right(), self.top_right()]
+            }
+            HBoxShape::RightTri(HBoxCorner::TopLeft) => {
+                vec![self.bottom_left(), self.top_right(), self.top_left()]
+            }
+            HBoxShape::RightTri(HBoxCorner::TopRight) => {
+                vec![self.bottom_right(), self.top_right(), self.top_left()]
+            }
+        }
+    }
+    /// Non-triangles are an inner box (possibly squashed to a line or point) grown by a radius.
+    /// Returns the min and max corners of the inner box, and the radius.
+    fn rounded_core(&self) -> (FVec2, FVec2, Fx) {
+        let radius = match self.shape {
+            HBoxShape::Circle | HBoxShape::Capsule => self.half_size.x.min(self.half_size.y),
+            HBoxShape::Rect | HBoxShape::RightTri(_) => Fx::ZERO,
+        };
+        let inner_half = match self.shape {
+            HBoxShape::Circle => FVec2::ZERO,
+            _ => self.half_size - FVec2::new(radius, radius),
+        };
+        (self.offset - inner_half, self.offset + inner_half, radius)
+    }
+    fn is_round(&self) -> bool {
+        matches!(self.shape, HBoxShape::Circle | HBoxShape::Capsule)
+    }
+    /// For triangles, the (not normalized) outward normal of the slanted side, and `d` such that
+    /// everything inside satisfies `normal.dot(p) <= d`
+    pub(crate) fn slope_plane(&self) -> Option<(FVec2, Fx)> {
+        let HBoxShape::RightTri(corner) = self.shape else {
+            return None;
+        };
+        let normal = FVec2::new(
+            if corner.is_left() {
+                self.half_size.y
+            } else {
+                -self.half_size.y
+            },
+            if corner.is_bottom() {
+                self.half_size.x
+            } else {
+                -self.half_size.x
+            },
+        );
+        let on_slope = if corner.is_left() == corner.is_bottom() {
+            self.top_left()
+        } else {
+            self.bottom_left()
+        };
+        Some((normal, normal.dot(on_slope)))
+    }
+    /// For triangles, the y of the slanted side at `x` (clamped to the box)
+    fn slope_y_at(&self, x: Fx) -> Fx {
+        let HBoxShape::RightTri(corner) = self.shape else {
+            return self.max_y();
+        };
+        let along = (x.clamp(self.min_x(), self.max_x()) - self.min_x()) * self.half_size.y
+            / self.half_size.x;
+        if corner.is_left() == corner.is_bottom() {
+            self.max_y() - along
+        } else {
+            self.min_y() + along
+        }
+    }
+    /// For triangles, the x of the slanted side at `y` (clamped to the box)
+    fn slope_x_at(&self, y: Fx) -> Fx {
+        let HBoxShape::RightTri(corner) = self.shape else {
+            return self.max_x();
+        };
+        let along = (y.clamp(self.min_y(), self.max_y()) - self.min_y()) * self.half_size.x
+            / self.half_size.y;
+        if corner.is_left() == corner.is_bottom() {
+            self.max_x() - along
+        } else {
+            self.min_x() + along
+        }
+    }
 }
 
 // I don't care that this is super verbose, and maybe inefficient. I want it to be correct.
@@ -148,6 +300,14 @@ impl HBox {
         x_overlap * y_overlap
     }
 
+    /// Area of the intersection of the two bounding boxes (zero if they don't overlap).
+    /// Unlike `area_overlapping_assuming_overlap`, right when one is inside the other.
+    pub fn area_intersecting(&self, rhs: &Self) -> Fx {
+        let x_overlap = self.max_x().min(rhs.max_x()) - self.min_x().max(rhs.min_x());
+        let y_overlap = self.max_y().min(rhs.max_y()) - self.min_y().max(rhs.min_y());
+        x_overlap.max(Fx::ZERO) * y_overlap.max(Fx::ZERO)
+    }
+
     /// Returns if the two hitboxes overlap
     pub fn overlaps_with(&self, rhs: &Self) -> bool {
         let my_x_min = self.min_x();
@@ -163,16 +323,47 @@ impl HBox {
         let dont_overlap_x = (my_x_max <= ox_min) || (ox_max <= my_x_min);
         let dont_overlap_y = (my_y_max <= oy_min) || (oy_max <= my_y_min);
 
-        !dont_overlap_x && !dont_overlap_y
+        if dont_overlap_x || dont_overlap_y {
+            return false;
+        }
+        let has_tri = matches!(self.shape, HBoxShape::RightTri(_))
+            || matches!(rhs.shape, HBoxShape::RightTri(_));
+        if !has_tri {
+            if !self.is_round() && !rhs.is_round() {
+                return true;
+            }
+            let (gap, radius) = self.rounded_gap(rhs);
+            return gap.length_squared() < radius * radius;
+        }
+        // Every edge is either axis-aligned or a slope, so the slopes are the only other axes
+        // that could separate us
+        let separated_by_slope = |tri: &Self, other: &Self| -> bool {
+            let Some((normal, d)) = tri.slope_plane() else {
+                return false;
+            };
+            other.vertices().iter().all(|v| normal.dot(*v) >= d)
+        };
+        !separated_by_slope(self, rhs) && !separated_by_slope(rhs, self)
     }
 
     /// If the two hitboxes overlap, return the vec that you need to move self to get it out of rhs
+    /// NOTE: When pushing out of a triangle, self is treated as a rect. Pushes out of floor (ceiling)
+    ///       slopes prefer going up (down) the slope, which is what lets things walk on them.
     pub fn get_push_out(&self, rhs: &Self) -> Option<FVec2> {
         // Hear me out: this might not be that inefficient.
         // Almost everytime we call this it returns none. Better to use simpler logic to get quick no in usual case.
         if !self.overlaps_with(rhs) {
             return None;
         }
+        match (self.shape, rhs.shape) {
+            (_, HBoxShape::RightTri(corner)) => return Some(self.get_push_out_of_tri(rhs, corner)),
+            (HBoxShape::RightTri(_), _) => {
+                // Moving us one way is the same as moving them the other way
+                return rhs.get_push_out(self).map(|push| -push);
+            }
+            (HBoxShape::Rect, HBoxShape::Rect) => (),
+            _ => return Some(self.get_push_out_rounded(rhs)),
+        }
 
         let my_x_min = self.min_x();
         let my_x_max = self.max_x();
@@ -208,4 +399,191 @@ impl HBox {
 
         Some(push)
     }
+
+    /// The outward normal of the surface of self that pushed something out by `push`.
+    /// Pushes out of a triangle are always axis-aligned, so when the push went out through the
+    /// slope (same sign as the slope normal on its axis) this is the slope normal instead.
+    pub(crate) fn surface_normal(&self, push: FVec2) -> FVec2 {
+        if let Some((slope, _)) = self.slope_plane() {
+            let through_slope = (push.x != Fx::ZERO && push.x.signum() == slope.x.signum())
+                || (push.y != Fx::ZERO && push.y.signum() == slope.y.signum());
+            if through_slope {
+                return slope.normalize_or_zero();
+            }
+        }
+        push.normalize_or_zero()
+    }
+
+    /// Push self (as a rect) out of a triangle. Assumes they overlap.
+    fn get_push_out_of_tri(&self, tri: &Self, corner: HBoxCorner) -> FVec2 {
+        let my_x_min = self.min_x();
+        let my_x_max = self.max_x();
+        let my_y_min = self.min_y();
+        let my_y_max = self.max_y();
+
+        // Out through the slope, vertically
+        let slope_ys = [tri.slope_y_at(my_x_min), tri.slope_y_at(my_x_max)];
+        let slope_ver_push = if corner.is_bottom() {
+            slope_ys[0].max(slope_ys[1]) - my_y_min
+        } else {
+            slope_ys[0].min(slope_ys[1]) - my_y_max
+        };
+        // Out through the flat side, vertically
+        let flat_ver_push = if corner.is_bottom() {
+            tri.min_y() - my_y_max
+        } else {
+            tri.max_y() - my_y_min
+        };
+        // Out through the slope, horizontally
+        let slope_xs = [tri.slope_x_at(my_y_min), tri.slope_x_at(my_y_max)];
+        let slope_hor_push = if corner.is_left() {
+            slope_xs[0].max(slope_xs[1]) - my_x_min
+        } else {
+            slope_xs[0].min(slope_xs[1]) - my_x_max
+        };
+        // Out through the flat side, horizontally
+        let flat_hor_push = if corner.is_left() {
+            tri.min_x() - my_x_max
+        } else {
+            tri.max_x() - my_x_min
+        };
+
+        let needed_ver_push = if flat_ver_push.abs() < slope_ver_push.abs() {
+            flat_ver_push
+        } else {
+            slope_ver_push
+        };
+        let needed_hor_push = if flat_hor_push.abs() < slope_hor_push.abs() {
+            flat_hor_push
+        } else {
+            slope_hor_push
+        };
+
+        if needed_hor_push.abs() < needed_ver_push.abs() {
+            FVec2::new(needed_hor_push, Fx::ZERO)
+        } else {
+            FVec2::new(Fx::ZERO, needed_ver_push)
+        }
+    }
+
+    /// How far apart the inner boxes of two non-triangles are on each axis (signed, pointing from
+    /// rhs towards self, zero where they overlap), and the sum of their radii
+    fn rounded_gap(&self, rhs: &Self) -> (FVec2, Fx) {
+        let (my_min, my_max, my_radius) = self.rounded_core();
+        let (o_min, o_max, o_radius) = rhs.rounded_core();
+        let axis_gap = |my_min: Fx, my_max: Fx, o_min: Fx, o_max: Fx| -> Fx {
+            if my_min > o_max {
+                my_min - o_max
+            } else if o_min > my_max {
+                my_max - o_min
+            } else {
+                Fx::ZERO
+            }
+        };
+        let gap = FVec2::new(
+            axis_gap(my_min.x, my_max.x, o_min.x, o_max.x),
+            axis_gap(my_min.y, my_max.y, o_min.y, o_max.y),
+        );
+        (gap, my_radius + o_radius)
+    }
+
+    /// Push self out of rhs when at least one of them is round. Assumes they overlap.
+    /// Round corners push diagonally, which is what lets capsules slide up over small ledges.
+    fn get_push_out_rounded(&self, rhs: &Self) -> FVec2 {
+        let (gap, radius) = self.rounded_gap(rhs);
+        if gap.x != Fx::ZERO && gap.y != Fx::ZERO {
+            // Corner to corner, push straight away from the closest point.
+            // NOTE: Not `gap / gap.length()`, the length underflows to zero right next to a corner.
+            let away = gap.try_normalize().expect("Non-zero gap");
+            return away * radius - gap;
+        }
+
+        let (my_min, my_max, _) = self.rounded_core();
+        let (o_min, o_max, _) = rhs.rounded_core();
+        let pick = |a: Fx, b: Fx| if a.abs() < b.abs() { a } else { b };
+        let needed_hor_push = pick(o_min.x - radius - my_max.x, o_max.x + radius - my_min.x);
+        let needed_ver_push = pick(o_min.y - radius - my_max.y, o_max.y + radius - my_min.y);
+
+        // Only an axis where the inner boxes already overlap can be escaped along the other one
+        if gap.y != Fx::ZERO {
+            FVec2::new(Fx::ZERO, needed_ver_push)
+        } else if gap.x != Fx::ZERO || needed_hor_push.abs() < needed_ver_push.abs() {
+            FVec2::new(needed_hor_push, Fx::ZERO)
+        } else {
+            FVec2::new(Fx::ZERO, needed_ver_push)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_out(me: HBox, other: HBox) -> FVec2 {
+        me.get_push_out(&other).expect("Should overlap")
+    }
+
+    #[test]
+    fn circles_push_straight_out_of_flat_sides() {
+        let floor = HBox::new(20, 4);
+        assert_eq!(
+            push_out(HBox::circle(4).with_offset(0, 3), floor.clone()),
+            FVec2::new(0, 3)
+        );
+        assert_eq!(
+            push_out(HBox::circle(4).with_offset(11, 0), floor),
+            FVec2::new(3, 0)
+        );
+    }
+
+    #[test]
+    fn round_corners_push_away_from_the_corner() {
+        let corner_at_one = HBox::new(8, 8).with_offset(5, 5);
+        let push = push_out(HBox::circle(4), corner_at_one.clone());
+        assert_eq!(push.x, push.y);
+        assert!(push.x < Fx::ZERO);
+        // Ends up exactly touching the corner
+        let from_corner = FVec2::new(-1, -1) + push;
+        assert!((from_corner.length() - fx!(4)).abs() < fx!(1) / 1024);
+
+        // Far enough in that the circle's center is (all but) on the corner
+        let tiny = Fx::DELTA;
+        let corner_at_zero = HBox::new(8, 8).with_offset(fx!(4) + tiny, fx!(4) + tiny);
+        let push = push_out(HBox::circle(4), corner_at_zero);
+        assert_eq!(push.x, push.y);
+        assert!((push.length() - fx!(4)).abs() < fx!(1) / 1024);
+    }
+
+    #[test]
+    fn capsules_slide_up_over_small_ledges() {
+        // Upright, with the bottom of its straight part just above the top of the ledge
+        let capsule = HBox::capsule(4, 12).with_offset(0, fx!(6.5));
+        let ledge = HBox::new(8, 2).with_offset(5, 1);
+        let push = push_out(capsule, ledge);
+        assert!(push.x < Fx::ZERO);
+        assert!(push.y > Fx::ZERO);
+
+        // Sunk down past the top, it's just a wall
+        let capsule = HBox::capsule(4, 12).with_offset(0, 5);
+        let ledge = HBox::new(8, 2).with_offset(5, 1);
+        assert_eq!(push_out(capsule, ledge), FVec2::new(-1, 0));
+    }
+
+    #[test]
+    fn round_things_go_up_slopes_like_their_box() {
+        let ramp = HBox::ramp_45(16, HBoxCorner::BottomLeft);
+        assert_eq!(
+            push_out(HBox::circle(4).with_offset(0, 3), ramp.clone()),
+            FVec2::new(0, 5)
+        );
+        assert_eq!(
+            push_out(HBox::capsule(8, 4).with_offset(0, 1), ramp.clone()),
+            FVec2::new(0, 5)
+        );
+        // And the other way around
+        assert_eq!(
+            push_out(ramp, HBox::circle(4).with_offset(0, 3)),
+            FVec2::new(0, -5)
+        );
+    }
 }
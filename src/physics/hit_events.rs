@@ -0,0 +1,77 @@
+//! Per-entity versions of the coll records, so an entity can handle its own hits with an
+//! observer instead of scanning `StaticColls`/`TriggerCollsGeneric` every frame.
+
+use bevy::prelude::*;
+
+use crate::physics::{
+    colls::{CollKey, StaticCollRec, StaticColls, TriggerCollRecGeneric, TriggerCollsGeneric},
+    triggers::TriggerKindTrait,
+};
+
+/// When attached to a ctrl, `StaticHit` and `TriggerHitGeneric` get triggered on it for every coll it's in.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+pub struct ObserveHits;
+
+/// Triggered on both the rx and tx ctrls (if they have `ObserveHits`) of every static coll
+#[derive(EntityEvent, Clone, Debug)]
+pub struct StaticHit {
+    /// The ctrl this was triggered on
+    pub entity: Entity,
+    /// True if `entity` is the rx of the coll, false if it's the tx
+    pub as_rx: bool,
+    pub key: CollKey,
+    pub rec: StaticCollRec,
+}
+
+/// Triggered on both the rx and tx ctrls (if they have `ObserveHits`) of every trigger coll
+#[derive(EntityEvent, Clone, Debug)]
+pub struct TriggerHitGeneric<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> {
+    /// The ctrl this was triggered on
+    pub entity: Entity,
+    /// True if `entity` is the rx of the coll, false if it's the tx
+    pub as_rx: bool,
+    pub key: CollKey,
+    pub rec: TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>,
+}
+
+pub(super) fn trigger_hits<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
+    static_colls: Res<StaticColls>,
+    trigger_colls: Res<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    observing: Query<(), With<ObserveHits>>,
+    mut commands: Commands,
+) {
+    if observing.is_empty() {
+        return;
+    }
+    // Keys are handed out in the order colls happen, so this keeps things deterministic
+    let mut static_keys = static_colls.map.keys().copied().collect::<Vec<_>>();
+    static_keys.sort();
+    for key in static_keys {
+        let rec = &static_colls.map[&key];
+        for (entity, as_rx) in [(rec.rx_ctrl, true), (rec.tx_ctrl, false)] {
+            if observing.contains(entity) {
+                commands.trigger(StaticHit {
+                    entity,
+                    as_rx,
+                    key,
+                    rec: rec.clone(),
+                });
+            }
+        }
+    }
+    let mut trigger_keys = trigger_colls.map.keys().copied().collect::<Vec<_>>();
+    trigger_keys.sort();
+    for key in trigger_keys {
+        let rec = &trigger_colls.map[&key];
+        for (entity, as_rx) in [(rec.rx_ctrl, true), (rec.tx_ctrl, false)] {
+            if observing.contains(entity) {
+                commands.trigger(TriggerHitGeneric {
+                    entity,
+                    as_rx,
+                    key,
+                    rec: rec.clone(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+/// Bitfields for filtering what interacts with what.
+/// Two things interact only if each one's `mask` includes a layer the other is a `member` of.
+/// By default everything is a member of layer 1 and interacts with every layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
+pub struct PhysicsLayers {
+    /// The layers this thing is on
+    pub member: u32,
+    /// The layers this thing interacts with
+    pub mask: u32,
+}
+impl Default for PhysicsLayers {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+impl PhysicsLayers {
+    pub const DEFAULT: Self = Self {
+        member: 1,
+        mask: u32::MAX,
+    };
+    /// On every layer, interacts with every layer. Handy as a "don't filter" for queries.
+    pub const ALL: Self = Self {
+        member: u32::MAX,
+        mask: u32::MAX,
+    };
+    /// On no layers, interacts with nothing
+    pub const NONE: Self = Self { member: 0, mask: 0 };
+
+    pub fn new(member: u32, mask: u32) -> Self {
+        Self { member, mask }
+    }
+    pub fn interacts_with(&self, other: &Self) -> bool {
+        (self.mask & other.member) != 0 && (other.mask & self.member) != 0
+    }
+}
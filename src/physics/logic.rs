@@ -1,35 +1,45 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::Parallel};
 
 use crate::{
     fx,
-    glue::{bullet_time::BulletTime, fvec::FVec2, Fx},
+    glue::{fvec::FVec2, Fx},
     physics::{
+        carry::{self, StaticCrush},
         colls::{StaticCollRec, StaticColls, TriggerCollRecGeneric, TriggerCollsGeneric},
-        dyno::Dyno,
-        hbox::HBox,
-        pos::Pos,
+        contacts, controller,
+        dyno::{self, ContinuousCollision, Dyno},
+        hbox::{HBox, HBoxShape},
+        hit_events,
+        layers::PhysicsLayers,
+        medium,
+        plugin::PhysicsDefaults,
+        pos::{self, Pos, RelPos},
         prelude::{
             StaticRx, StaticRxKind, StaticTx, StaticTxKind, TriggerKindTrait, TriggerRxGeneric,
             TriggerTxGeneric,
         },
+        pushable::{self, Pushable},
+        tiles::TileColliders,
+        timestep::{self, PhysicsDelta},
         PhysicsSet,
     },
+    prelude::{HashMap, HashSet},
 };
 
-use super::spat_hash::{SpatHash, SpatHashStaticTx, SpatHashTriggerTx, SpatKeys};
+use super::{
+    query, snapshot,
+    spat_hash::{SpatHash, SpatHashStaticTx, SpatHashTriggerTx, SpatKeys},
+    trigger_events::update_trigger_pairs,
+};
 
 /// A helpful function to make sure physics things exist as we expect them to
 #[cfg(debug_assertions)]
 fn invariants(
     dyno_without_pos: Query<Entity, (With<Dyno>, Without<Pos>)>,
     static_rx_n_tx: Query<Entity, (With<StaticRx>, With<StaticTx>)>,
-    moving_static_tx_vert_only: Query<&Dyno, With<StaticTx>>,
 ) {
     debug_assert!(dyno_without_pos.is_empty());
     debug_assert!(static_rx_n_tx.is_empty());
-    for dyno in &moving_static_tx_vert_only {
-        debug_assert!(dyno.vel.x.abs() == Fx::ZERO);
-    }
 }
 
 fn reset_colls_every_frame<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
@@ -58,7 +68,7 @@ fn reset_colls_every_frame<TriggerRxKind: TriggerKindTrait, TriggerTxKind: Trigg
 
 /// Moves dynos that have no statics and no trigger receivers
 fn move_uninteresting_dynos<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
-    bullet_time: Res<BulletTime>,
+    physics_delta: Res<PhysicsDelta>,
     mut ents: Query<
         (
             Entity,
@@ -76,7 +86,7 @@ fn move_uninteresting_dynos<TriggerRxKind: TriggerKindTrait, TriggerTxKind: Trig
     mut spat_hash_trigger_tx: ResMut<SpatHash<SpatHashTriggerTx>>,
 ) {
     for (eid, dyno, mut pos, ttx, mut spat_keys) in &mut ents {
-        *pos += dyno.vel * bullet_time.delta_secs();
+        *pos += dyno.vel * physics_delta.delta_secs();
         match (ttx, spat_keys.as_mut()) {
             (Some(ttx), Some(spat_keys)) => {
                 let new_keys = spat_hash_trigger_tx.update(
@@ -92,44 +102,166 @@ fn move_uninteresting_dynos<TriggerRxKind: TriggerKindTrait, TriggerTxKind: Trig
     }
 }
 
-/// Moves static txs
+/// Moves static txs, carrying anything riding them and pushing anything in their way
 fn move_static_txs<TriggerTxKind: TriggerKindTrait>(
-    bullet_time: Res<BulletTime>,
-    mut ents: Query<
-        (
-            Entity,
-            &Dyno,
-            &mut Pos,
-            &StaticTx,
-            &mut SpatKeys<SpatHashStaticTx>,
-            Option<&TriggerTxGeneric<TriggerTxKind>>,
-            Option<&mut SpatKeys<SpatHashTriggerTx>>,
-        ),
-        Without<StaticRx>,
-    >,
+    mut commands: Commands,
+    physics_delta: Res<PhysicsDelta>,
+    physics_defaults: Res<PhysicsDefaults>,
+    movers_q: Query<(Entity, Option<&Dyno>, &StaticTx), Without<StaticRx>>,
+    stx_q: Query<(Entity, &StaticTx)>,
+    srx_q: Query<(Entity, &StaticRx)>,
+    ttx_q: Query<&TriggerTxGeneric<TriggerTxKind>>,
+    mut pos_q: Query<&mut Pos>,
+    mut stx_spat_keys_q: Query<&mut SpatKeys<SpatHashStaticTx>>,
+    mut ttx_spat_keys_q: Query<&mut SpatKeys<SpatHashTriggerTx>>,
     mut spat_hash_static_tx: ResMut<SpatHash<SpatHashStaticTx>>,
     mut spat_hash_trigger_tx: ResMut<SpatHash<SpatHashTriggerTx>>,
+    tiles: Res<TileColliders>,
 ) {
-    for (eid, dyno, mut pos, stx, mut stx_spat_keys, ttx, mut ttx_spat_keys) in &mut ents {
-        *pos += dyno.vel * bullet_time.delta_secs();
-        let new_stx_spat_keys = spat_hash_static_tx.update(
-            eid,
-            &stx_spat_keys,
-            pos.clone(),
-            stx.comps.iter().map(|c| c.hbox.clone()).collect(),
-        );
-        *stx_spat_keys = new_stx_spat_keys;
-        match (ttx, ttx_spat_keys.as_mut()) {
-            (Some(ttx), Some(ttx_spat_keys)) => {
-                let new_ttx_spat_keys = spat_hash_trigger_tx.update(
-                    eid,
-                    &ttx_spat_keys,
-                    pos.clone(),
-                    ttx.comps.iter().map(|c| c.hbox.clone()).collect(),
-                );
-                **ttx_spat_keys = new_ttx_spat_keys;
+    // Only built once something actually moves
+    let mut rx_index: Option<carry::RxIndex> = None;
+    for (eid, dyno, stx) in &movers_q {
+        let delta = dyno.map(|dyno| dyno.vel).unwrap_or_default() * physics_delta.delta_secs();
+        let surface_delta = FVec2::new(stx.surface_vel * physics_delta.delta_secs(), Fx::ZERO);
+        if delta == FVec2::ZERO && surface_delta == FVec2::ZERO {
+            continue;
+        }
+        let old_pos = pos_q.get(eid).expect("Missing pos on stx").clone();
+        let new_pos = old_pos + delta;
+
+        // Figure out who's riding us before anything moves
+        let old_tx_comps = stx
+            .comps
+            .iter()
+            .map(|comp| {
+                (
+                    comp.kind,
+                    comp.layers,
+                    comp.hbox.translated(old_pos.as_fvec2()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let rx_index = rx_index.get_or_insert_with(|| {
+            carry::RxIndex::new(
+                srx_q
+                    .iter()
+                    .filter_map(|(rx_eid, srx)| Some((rx_eid, *pos_q.get(rx_eid).ok()?, srx))),
+            )
+        });
+        let riders = rx_index
+            .get_near(old_tx_comps.iter().map(|(_, _, thbox)| thbox))
+            .into_iter()
+            .filter_map(|rx_eid| {
+                let (_, srx) = srx_q.get(rx_eid).ok()?;
+                let rx_pos = pos_q.get(rx_eid).ok()?;
+                let rx_thboxes = carry::carried_thboxes(srx, rx_pos.clone());
+                carry::get_riding(&rx_thboxes, &old_tx_comps, srx.side_carry, delta.y)
+                    .map(|riding| (rx_eid, srx, riding))
+            })
+            .collect::<Vec<_>>();
+
+        // Move ourselves
+        *pos_q.get_mut(eid).expect("Missing pos on stx") = new_pos.clone();
+        if let Ok(mut stx_spat_keys) = stx_spat_keys_q.get_mut(eid) {
+            let new_stx_spat_keys = spat_hash_static_tx.update(
+                eid,
+                &stx_spat_keys,
+                new_pos.clone(),
+                stx.comps.iter().map(|c| c.hbox.clone()).collect(),
+            );
+            *stx_spat_keys = new_stx_spat_keys;
+        }
+        if let (Ok(ttx), Ok(mut ttx_spat_keys)) = (ttx_q.get(eid), ttx_spat_keys_q.get_mut(eid)) {
+            let new_ttx_spat_keys = spat_hash_trigger_tx.update(
+                eid,
+                &ttx_spat_keys,
+                new_pos.clone(),
+                ttx.comps.iter().map(|c| c.hbox.clone()).collect(),
+            );
+            *ttx_spat_keys = new_ttx_spat_keys;
+        }
+
+        // Carry the riders. Vertical movement of things on top is handled when resolving collisions.
+        let top_riders = riders
+            .iter()
+            .filter(|(_, _, riding)| *riding == carry::Riding::Top)
+            .map(|(rx_eid, _, _)| *rx_eid)
+            .collect::<HashSet<_>>();
+        for (rx_eid, srx, riding) in riders {
+            let carry_delta = match riding {
+                carry::Riding::Top => FVec2::new(delta.x, Fx::ZERO) + surface_delta,
+                carry::Riding::Side => delta,
+            };
+            carry::move_rx_blocked(
+                rx_eid,
+                srx,
+                carry_delta,
+                eid,
+                physics_defaults.max_step,
+                &mut pos_q,
+                &stx_q,
+                &spat_hash_static_tx,
+                &tiles,
+            );
+            if let Ok(rx_pos) = pos_q.get(rx_eid) {
+                rx_index.update(rx_eid, *rx_pos, srx);
+            }
+        }
+
+        // Push anything we moved into out of the way, crushing it if there's nowhere to go.
+        // Things on top already came along sideways, and if we rose into them that's for
+        // collision resolution to push up, not for us to shove off the side.
+        if delta.x == Fx::ZERO {
+            continue;
+        }
+        let new_solid_thboxes = stx
+            .comps
+            .iter()
+            .filter(|comp| comp.kind == StaticTxKind::Solid)
+            .map(|comp| (comp.layers, comp.hbox.translated(new_pos.as_fvec2())))
+            .collect::<Vec<_>>();
+        for rx_eid in rx_index.get_near(new_solid_thboxes.iter().map(|(_, thbox)| thbox)) {
+            if top_riders.contains(&rx_eid) {
+                continue;
+            }
+            let (Ok((_, srx)), Ok(rx_pos)) = (srx_q.get(rx_eid), pos_q.get(rx_eid).cloned()) else {
+                continue;
+            };
+            let rx_thboxes = carry::carried_thboxes(srx, rx_pos.clone());
+            let mut push = Fx::ZERO;
+            for (rx_layers, rx_thbox) in &rx_thboxes {
+                for (tx_layers, tx_thbox) in &new_solid_thboxes {
+                    if !rx_layers.interacts_with(tx_layers) {
+                        continue;
+                    }
+                    if let Some(push_hor) = carry::get_push_hor(rx_thbox, tx_thbox, delta.x) {
+                        if push_hor.abs() > push.abs() {
+                            push = push_hor;
+                        }
+                    }
+                }
+            }
+            if push == Fx::ZERO {
+                continue;
+            }
+            let pushed_pos = rx_pos + FVec2::new(push, Fx::ZERO);
+            *pos_q.get_mut(rx_eid).expect("Missing pos on srx") = pushed_pos.clone();
+            rx_index.update(rx_eid, pushed_pos, srx);
+            let pushed_thboxes = carry::carried_thboxes(srx, pushed_pos);
+            if let Some(against_ctrl) = carry::first_solid_overlap(
+                &pushed_thboxes,
+                eid,
+                &pos_q,
+                &stx_q,
+                &spat_hash_static_tx,
+                &tiles,
+            ) {
+                commands.trigger(StaticCrush {
+                    entity: rx_eid,
+                    tx_ctrl: eid,
+                    against_ctrl,
+                });
             }
-            _ => (),
         }
     }
 }
@@ -147,16 +279,19 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
     dyno_q: &Query<&mut Dyno>,
     stx_q: &Query<(Entity, &mut StaticTx)>,
     ttx_q: &Query<(Entity, &mut TriggerTxGeneric<TriggerTxKind>)>,
-    static_colls: &mut ResMut<StaticColls>,
-    trigger_colls: &mut ResMut<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    static_colls: &mut Vec<StaticCollRec>,
+    trigger_colls: &mut Vec<TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>>,
     spat_hash_stx: &SpatHash<SpatHashStaticTx>,
     spat_hash_ttx: &SpatHash<SpatHashTriggerTx>,
+    tiles: &TileColliders,
+    max_step: Fx,
 ) {
     // Handle static collisions
     struct StaticCollCandidate {
         eid: Entity,
         pos: Pos,
         kind: StaticTxKind,
+        layers: PhysicsLayers,
         thbox: HBox,
     }
 
@@ -177,12 +312,29 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
                         eid,
                         pos: pos.clone(),
                         kind: comp.kind,
+                        layers: comp.layers,
                         thbox: comp.hbox.translated(pos.as_fvec2()),
                     })
                 })
                 .filter(|candidate| candidate.eid != my_eid)
+                .filter(|candidate| my_srx_comp.layers.interacts_with(&candidate.layers))
                 .filter(|candidate| my_thbox.overlaps_with(&candidate.thbox))
                 .collect::<Vec<_>>();
+            // Tiles act just like a tx per cell
+            candidates.extend(
+                tiles
+                    .get_overlapping(&[my_thbox.clone()])
+                    .into_iter()
+                    .filter(|tile| my_srx_comp.layers.interacts_with(&tile.layers))
+                    .filter(|tile| my_thbox.overlaps_with(&tile.thbox))
+                    .map(|tile| StaticCollCandidate {
+                        eid: tile.ctrl,
+                        pos: tile.pos,
+                        kind: tile.kind,
+                        layers: tile.layers,
+                        thbox: tile.thbox,
+                    }),
+            );
             // Sorting by the amount of overlap allows sliding in the "right" way (I think)
             candidates.sort_by(|a, b| {
                 let dist_a = a.thbox.area_overlapping_assuming_overlap(&my_thbox);
@@ -197,7 +349,11 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
 
                 // COLLISION ACTUALLY HAPPENING
                 let tx_dyno = dyno_q.get(candidate.eid).cloned().unwrap_or_default();
-                let mut old_perp = if push.x.abs() != Fx::ZERO {
+                let mut old_perp = if push.x != Fx::ZERO && push.y != Fx::ZERO {
+                    // Round hboxes can get pushed diagonally, split along the push
+                    let normal = push.try_normalize().expect("Non-zero push");
+                    normal * my_vel.dot(normal)
+                } else if push.x.abs() != Fx::ZERO {
                     FVec2::new(my_vel.x, Fx::ZERO)
                 } else {
                     FVec2::new(Fx::ZERO, my_vel.y)
@@ -219,6 +375,8 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
                     tx_ctrl: candidate.eid,
                     tx_kind: candidate.kind,
                     tx_hbox: candidate.thbox.get_marker(),
+                    tx_normal: candidate.thbox.surface_normal(push),
+                    pass_ignored: false,
                 };
 
                 let mut do_push = |grr: &mut HBox| {
@@ -226,60 +384,45 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
                     *grr = grr.translated(push);
                 };
 
-                match (my_srx_comp.kind, candidate.kind) {
-                    (StaticRxKind::Default, StaticTxKind::Solid) => {
-                        static_colls.insert(coll_rec);
+                // Pass-through txs only stop things moving into their solid side that weren't already past it.
+                // Nothing moves more than `max_step` between resolves, so anything deeper was already inside.
+                if let Some(side) = candidate.kind.solid_side() {
+                    let into_side = push.dot(side);
+                    if into_side <= Fx::ZERO
+                        || into_side > max_step
+                        || old_perp.dot(side) >= Fx::ZERO
+                    {
+                        continue;
+                    }
+                    if my_srx.is_ignoring_pass_through(candidate.eid) {
+                        static_colls.push(StaticCollRec {
+                            pass_ignored: true,
+                            ..coll_rec
+                        });
+                        continue;
+                    }
+                }
+
+                match my_srx_comp.kind {
+                    StaticRxKind::Default => {
+                        static_colls.push(coll_rec);
                         do_push(&mut my_thbox);
-                        *my_vel = old_par + FVec2::new(0, tx_dyno.vel.y);
+                        *my_vel = old_par + FVec2::new(Fx::ZERO, tx_dyno.vel.y);
                         if old_perp.dot(push) > Fx::ZERO {
                             *my_vel += old_perp;
                         }
                     }
-                    (StaticRxKind::Default, StaticTxKind::PassUp) => {
-                        if push.y > 0.0
-                            && old_perp.y < 0.0
-                            && candidate.thbox.max_y() - fx!(1) < my_thbox.min_y()
-                        {
-                            static_colls.insert(coll_rec);
-                            do_push(&mut my_thbox);
-                            *my_vel = old_par + FVec2::new(Fx::ZERO, tx_dyno.vel.y);
-                            if old_perp.dot(push) > Fx::ZERO {
-                                *my_vel += old_perp;
-                            }
-                        }
-                    }
-                    (StaticRxKind::Observe, StaticTxKind::Solid) => {
-                        static_colls.insert(coll_rec);
+                    StaticRxKind::Observe => {
+                        static_colls.push(coll_rec);
                     }
-                    (StaticRxKind::Observe, StaticTxKind::PassUp) => {
-                        if push.y > 0.0
-                            && old_perp.y < 0.0
-                            && candidate.thbox.max_y() - fx!(1) < my_thbox.min_y()
-                        {
-                            static_colls.insert(coll_rec);
-                        }
-                    }
-                    (StaticRxKind::Bounce { perp, par }, StaticTxKind::Solid) => {
-                        static_colls.insert(coll_rec);
+                    StaticRxKind::Bounce { perp, par } => {
+                        static_colls.push(coll_rec);
                         do_push(&mut my_thbox);
                         *my_vel = old_par * par + FVec2::new(Fx::ZERO, tx_dyno.vel.y);
                         if old_perp.dot(push) > Fx::ZERO {
                             *my_vel += old_perp * perp;
                         }
                     }
-                    (StaticRxKind::Bounce { perp, par }, StaticTxKind::PassUp) => {
-                        if push.y > 0.0
-                            && old_perp.y < 0.0
-                            && candidate.thbox.max_y() - fx!(1) < my_thbox.min_y()
-                        {
-                            static_colls.insert(coll_rec);
-                            do_push(&mut my_thbox);
-                            *my_vel = old_par * par + FVec2::new(Fx::ZERO, tx_dyno.vel.y);
-                            if old_perp.dot(push) > Fx::ZERO {
-                                *my_vel += old_perp * perp;
-                            }
-                        }
-                    }
                 }
             }
         }
@@ -290,6 +433,7 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
         eid: Entity,
         pos: Pos,
         kind: InnerTriggerTxKind,
+        layers: PhysicsLayers,
         thbox: HBox,
     }
 
@@ -310,10 +454,12 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
                         eid,
                         pos: pos.clone(),
                         kind: comp.kind.clone(),
+                        layers: comp.layers,
                         thbox: comp.hbox.translated(pos.as_fvec2()),
                     })
                 })
                 .filter(|candidate| candidate.eid != my_eid)
+                .filter(|candidate| my_trx_comp.layers.interacts_with(&candidate.layers))
                 .filter(|candidate| my_thbox.overlaps_with(&candidate.thbox));
             for candidate in candidates {
                 let coll_rec = TriggerCollRecGeneric {
@@ -326,10 +472,225 @@ fn resolve_collisions<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKin
                     tx_kind: candidate.kind,
                     tx_hbox: candidate.thbox.get_marker(),
                 };
-                trigger_colls.insert(coll_rec);
+                trigger_colls.push(coll_rec);
+            }
+        }
+    }
+}
+
+/// How far below this srx the ground is, as long as it's within `max_snap`.
+/// Used to keep things glued to slopes when walking down them.
+fn get_ground_snap(
+    my_eid: Entity,
+    my_pos: &Pos,
+    my_srx: &StaticRx,
+    max_snap: Fx,
+    pos_q: &Query<&mut Pos>,
+    stx_q: &Query<(Entity, &mut StaticTx)>,
+    spat_hash_stx: &SpatHash<SpatHashStaticTx>,
+    tiles: &TileColliders,
+) -> Option<Fx> {
+    let mut result: Option<Fx> = None;
+    for my_srx_comp in my_srx
+        .comps
+        .iter()
+        .filter(|comp| comp.kind == StaticRxKind::Default)
+    {
+        let probe = my_srx_comp
+            .hbox
+            .translated(my_pos.as_fvec2() - FVec2::new(Fx::ZERO, max_snap));
+        let stx_keys = spat_hash_stx.get_keys(Pos::default(), vec![probe.clone()]);
+        let mut txs = vec![];
+        for eid in spat_hash_stx.get_eids(stx_keys) {
+            if eid == my_eid {
+                continue;
+            }
+            let (Ok((_, stx)), Ok(tx_pos)) = (stx_q.get(eid), pos_q.get(eid)) else {
+                continue;
+            };
+            txs.extend(
+                stx.comps
+                    .iter()
+                    .filter(|comp| my_srx_comp.layers.interacts_with(&comp.layers))
+                    .map(|comp| (eid, comp.kind, comp.hbox.translated(tx_pos.as_fvec2()))),
+            );
+        }
+        txs.extend(
+            tiles
+                .get_overlapping(&[probe.clone()])
+                .into_iter()
+                .filter(|tile| my_srx_comp.layers.interacts_with(&tile.layers))
+                .map(|tile| (tile.ctrl, tile.kind, tile.thbox)),
+        );
+        for (tx_eid, tx_kind, tx_thbox) in txs {
+            let Some(push) = probe.get_push_out(&tx_thbox) else {
+                continue;
+            };
+            // Same as `resolve_collisions`, pass-through stuff is only ground from its solid side,
+            // and not at all while we're dropping through (or ignoring) it
+            if let Some(side) = tx_kind.solid_side() {
+                if push.dot(side) <= Fx::ZERO || my_srx.is_ignoring_pass_through(tx_eid) {
+                    continue;
+                }
+            }
+            if push.y > Fx::ZERO && push.y <= max_snap {
+                let snap = max_snap - push.y;
+                result = Some(result.map_or(snap, |other| other.min(snap)));
+            }
+        }
+    }
+    result
+}
+
+/// Whether there's a slope this srx could be standing on, either right below it or somewhere along
+/// the next `dist` it moves horizontally (in the direction of `dir_x`), within `max_snap` below it.
+/// Snapping to the ground only matters on slopes, so everywhere else we can skip probing for it.
+fn is_near_slope(
+    my_eid: Entity,
+    my_pos: &Pos,
+    my_srx: &StaticRx,
+    dir_x: Fx,
+    dist: Fx,
+    max_snap: Fx,
+    pos_q: &Query<&mut Pos>,
+    stx_q: &Query<(Entity, &mut StaticTx)>,
+    spat_hash_stx: &SpatHash<SpatHashStaticTx>,
+) -> bool {
+    let reach = dir_x * dist;
+    for my_srx_comp in my_srx
+        .comps
+        .iter()
+        .filter(|comp| comp.kind == StaticRxKind::Default)
+    {
+        let my_thbox = my_srx_comp.hbox.translated(my_pos.as_fvec2());
+        let min = FVec2::new(
+            my_thbox.min_x() + reach.min(Fx::ZERO),
+            my_thbox.min_y() - max_snap,
+        );
+        let max = FVec2::new(my_thbox.max_x() + reach.max(Fx::ZERO), my_thbox.max_y());
+        for eid in spat_hash_stx.get_eids(spat_hash_stx.get_region_keys(min, max)) {
+            if eid == my_eid {
+                continue;
+            }
+            let (Ok((_, stx)), Ok(tx_pos)) = (stx_q.get(eid), pos_q.get(eid)) else {
+                continue;
+            };
+            // Tiles are always full cells, so only txs can be slopes
+            let near = stx.comps.iter().any(|comp| {
+                let tx_thbox = comp.hbox.translated(tx_pos.as_fvec2());
+                matches!(comp.hbox.get_shape(), HBoxShape::RightTri(_))
+                    && my_srx_comp.layers.interacts_with(&comp.layers)
+                    && tx_thbox.min_x() <= max.x
+                    && min.x <= tx_thbox.max_x()
+                    && tx_thbox.min_y() <= max.y
+                    && min.y <= tx_thbox.max_y()
+            });
+            if near {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// How far this rx can move along `dir` (up to `max_dist`) before it first touches a tx it would
+/// collide with. Lets `ContinuousCollision` stuff take one big step instead of inching.
+fn get_sweep_dist<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
+    my_eid: Entity,
+    my_pos: &Pos,
+    my_srx: Option<&StaticRx>,
+    my_trx: Option<&TriggerRxGeneric<TriggerRxKind>>,
+    dir: FVec2,
+    max_dist: Fx,
+    pos_q: &Query<&mut Pos>,
+    stx_q: &Query<(Entity, &mut StaticTx)>,
+    ttx_q: &Query<(Entity, &mut TriggerTxGeneric<TriggerTxKind>)>,
+    spat_hash_stx: &SpatHash<SpatHashStaticTx>,
+    spat_hash_ttx: &SpatHash<SpatHashTriggerTx>,
+    tiles: &TileColliders,
+) -> Option<Fx> {
+    // The region covered by the movers over the whole sweep
+    let swept_region = |movers: &[(PhysicsLayers, HBox)]| {
+        let shift = dir * max_dist;
+        let (mut min, mut max) = (FVec2::new(Fx::MAX, Fx::MAX), FVec2::new(Fx::MIN, Fx::MIN));
+        for (_, thbox) in movers {
+            for (lo, hi) in [
+                (thbox.bottom_left(), thbox.top_right()),
+                (thbox.bottom_left() + shift, thbox.top_right() + shift),
+            ] {
+                min = FVec2::new(min.x.min(lo.x), min.y.min(lo.y));
+                max = FVec2::new(max.x.max(hi.x), max.y.max(hi.y));
+            }
+        }
+        (min, max)
+    };
+    let mut result: Option<Fx> = None;
+    if let Some(my_srx) = my_srx {
+        let movers = my_srx
+            .comps
+            .iter()
+            .map(|comp| (comp.layers, comp.hbox.translated(my_pos.as_fvec2())))
+            .collect::<Vec<_>>();
+        let mut others = vec![];
+        let (min, max) = swept_region(&movers);
+        for eid in spat_hash_stx.get_eids(spat_hash_stx.get_region_keys(min, max)) {
+            if eid == my_eid {
+                continue;
+            }
+            let (Ok((_, stx)), Ok(tx_pos)) = (stx_q.get(eid), pos_q.get(eid)) else {
+                continue;
+            };
+            // Pass-through stuff only matters if we're heading into the side that stops us
+            others.extend(
+                stx.comps
+                    .iter()
+                    .filter(|comp| {
+                        comp.kind
+                            .solid_side()
+                            .is_none_or(|side| side.dot(dir) < Fx::ZERO)
+                    })
+                    .map(|comp| (comp.layers, comp.hbox.translated(tx_pos.as_fvec2()))),
+            );
+        }
+        others.extend(
+            tiles
+                .get_region(min, max)
+                .into_iter()
+                .filter(|tile| {
+                    tile.kind
+                        .solid_side()
+                        .is_none_or(|side| side.dot(dir) < Fx::ZERO)
+                })
+                .map(|tile| (tile.layers, tile.thbox)),
+        );
+        result = query::sweep_dist(&movers, dir, max_dist, &others);
+    }
+    if let Some(my_trx) = my_trx {
+        let movers = my_trx
+            .comps
+            .iter()
+            .map(|comp| (comp.layers, comp.hbox.translated(my_pos.as_fvec2())))
+            .collect::<Vec<_>>();
+        let mut others = vec![];
+        let (min, max) = swept_region(&movers);
+        for eid in spat_hash_ttx.get_eids(spat_hash_ttx.get_region_keys(min, max)) {
+            if eid == my_eid {
+                continue;
             }
+            let (Ok((_, ttx)), Ok(tx_pos)) = (ttx_q.get(eid), pos_q.get(eid)) else {
+                continue;
+            };
+            others.extend(
+                ttx.comps
+                    .iter()
+                    .map(|comp| (comp.layers, comp.hbox.translated(tx_pos.as_fvec2()))),
+            );
+        }
+        if let Some(dist) = query::sweep_dist(&movers, dir, max_dist, &others) {
+            result = Some(result.map_or(dist, |other| other.min(dist)));
         }
     }
+    result
 }
 
 /// As we resolve collisions, we create the collisions records but don't put the corresponding
@@ -360,10 +721,179 @@ fn populate_ctrl_coll_keys<TriggerRxKind: TriggerKindTrait, TriggerTxKind: Trigg
     }
 }
 
-/// Moves the interesting stuff and handles collisions
+/// Moves a single interesting body along its velocity, resolving collisions as it goes.
+/// Only reads the world. Records go in `static_colls`/`trigger_colls` (in the order they happened),
+/// and anything that needs to change other entities (pushing) happens in `after_resolve`.
+fn move_body<'w, 's, TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait, PosQ>(
+    eid: Entity,
+    continuous: bool,
+    scratch_pos: &mut Pos,
+    scratch_vel: &mut FVec2,
+    srx: Option<(Entity, &StaticRx)>,
+    trx: Option<(Entity, &TriggerRxGeneric<TriggerRxKind>)>,
+    delta_secs: Fx,
+    max_step: Fx,
+    pos_q: &mut PosQ,
+    dyno_q: &Query<&mut Dyno>,
+    stx_q: &Query<(Entity, &mut StaticTx)>,
+    ttx_q: &Query<(Entity, &mut TriggerTxGeneric<TriggerTxKind>)>,
+    spat_hash_stx: &SpatHash<SpatHashStaticTx>,
+    spat_hash_ttx: &SpatHash<SpatHashTriggerTx>,
+    tiles: &TileColliders,
+    static_colls: &mut Vec<StaticCollRec>,
+    trigger_colls: &mut Vec<TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut after_resolve: impl FnMut(&mut PosQ, &mut Pos, &mut FVec2, &mut Vec<StaticCollRec>),
+) where
+    PosQ: std::ops::Deref<Target = Query<'w, 's, &'static mut Pos>>,
+{
+    debug_assert!(srx.is_some() || trx.is_some());
+    // Inch
+    macro_rules! call_resolve_collisions {
+        () => {{
+            resolve_collisions(
+                eid,
+                scratch_pos,
+                scratch_vel,
+                srx,
+                trx,
+                pos_q,
+                dyno_q,
+                stx_q,
+                ttx_q,
+                static_colls,
+                trigger_colls,
+                spat_hash_stx,
+                spat_hash_ttx,
+                tiles,
+                max_step,
+            );
+            after_resolve(pos_q, scratch_pos, scratch_vel, static_colls);
+        }};
+    }
+    macro_rules! get_ground_snap {
+        ($max_snap:expr) => {{
+            srx.and_then(|(_, srx)| {
+                get_ground_snap(
+                    eid,
+                    scratch_pos,
+                    srx,
+                    $max_snap,
+                    pos_q,
+                    stx_q,
+                    spat_hash_stx,
+                    tiles,
+                )
+            })
+        }};
+    }
+    // Continuous stuff steps right up to (and just into) the next thing it would hit,
+    // everything else inches along at most `max_step` at a time
+    macro_rules! get_step {
+        ($dir:expr, $remaining:expr) => {{
+            if continuous {
+                let slop = fx!(1) / 64;
+                get_sweep_dist(
+                    eid,
+                    scratch_pos,
+                    srx.map(|(_, srx)| srx),
+                    trx.map(|(_, trx)| trx),
+                    $dir,
+                    $remaining,
+                    pos_q,
+                    stx_q,
+                    ttx_q,
+                    spat_hash_stx,
+                    spat_hash_ttx,
+                    tiles,
+                )
+                .map_or($remaining, |dist| (dist + slop).min($remaining))
+            } else {
+                max_step.min($remaining)
+            }
+        }};
+    }
+    // Resolve collisions once always so stationary objects are still pushed out of each other
+    call_resolve_collisions!();
+    // Inch horizontally
+    let mut amt_moved_hor: Fx = Fx::ZERO;
+    let max_inch_hor = scratch_vel.x.abs() * delta_secs;
+    // Probing for the ground every inch adds up, and it only matters when there's a slope around
+    let near_slope = srx.is_some_and(|(_, srx)| {
+        is_near_slope(
+            eid,
+            scratch_pos,
+            srx,
+            scratch_vel.x.signum(),
+            max_inch_hor.min(scratch_vel.x.abs()),
+            max_step,
+            pos_q,
+            stx_q,
+            spat_hash_stx,
+        )
+    });
+    while amt_moved_hor < max_inch_hor.min(scratch_vel.x.abs()) {
+        let dont_overshoot = (max_inch_hor.min(scratch_vel.x.abs()) - amt_moved_hor).max(Fx::ZERO);
+        let moving_this_step = get_step!(FVec2::X * scratch_vel.x.signum(), dont_overshoot);
+        // If we're standing on something, stay on it when walking down (up to 45°) slopes
+        let was_grounded =
+            near_slope && scratch_vel.y <= Fx::ZERO && get_ground_snap!(fx!(1) / 64).is_some();
+        amt_moved_hor += moving_this_step;
+        scratch_pos.x += scratch_vel.x.signum() * moving_this_step;
+        if was_grounded {
+            if let Some(snap) = get_ground_snap!(moving_this_step.min(max_step)) {
+                scratch_pos.y -= snap;
+            }
+        }
+        call_resolve_collisions!();
+    }
+    // Then inch vertically
+    let mut amt_moved_ver: Fx = Fx::ZERO;
+    let max_inch_ver = scratch_vel.y.abs() * delta_secs;
+    while amt_moved_ver < max_inch_ver.min(scratch_vel.y.abs()) {
+        let dont_overshoot = (max_inch_ver.min(scratch_vel.y.abs()) - amt_moved_ver).max(Fx::ZERO);
+        let moving_this_step = get_step!(FVec2::Y * scratch_vel.y.signum(), dont_overshoot);
+        amt_moved_ver += moving_this_step;
+        scratch_pos.y += scratch_vel.y.signum() * moving_this_step;
+        call_resolve_collisions!();
+    }
+    // NOTE: Why do this (inch horizontally then vertically)? Stops bugs going up and down against wall.
+    // ^read: celeste does this
+}
+
+/// What a body that moved in parallel ended up doing, waiting to be written back
+struct MovedBody<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> {
+    pos: Pos,
+    vel: FVec2,
+    static_colls: Vec<StaticCollRec>,
+    trigger_colls: Vec<TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>>,
+}
+
+/// Whether the box from `min` to `max` touches any of the areas bodies near pushables could reach.
+/// Pushable areas are grown by `shove`, how far they could get pushed.
+fn touches_pushable_area(
+    min: FVec2,
+    max: FVec2,
+    areas: &[(FVec2, FVec2, bool)],
+    shove: Fx,
+) -> bool {
+    areas.iter().any(|(area_min, area_max, pushable)| {
+        let pad = if *pushable { shove } else { Fx::ZERO };
+        min.x <= area_max.x + pad
+            && area_min.x - pad <= max.x
+            && min.y <= area_max.y + pad
+            && area_min.y - pad <= max.y
+    })
+}
+
+/// Moves the interesting stuff and handles collisions.
+/// Bodies that can't affect (or be affected by) any other body moving this step go in parallel,
+/// the rest go one at a time. Either way results are written back in entity order, so coll keys
+/// come out the same no matter how many threads there are.
 fn move_interesting_dynos<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
-    bullet_time: Res<BulletTime>,
-    mut pos_q: Query<&mut Pos>,
+    physics_delta: Res<PhysicsDelta>,
+    physics_defaults: Res<PhysicsDefaults>,
+    // Spelled out so `move_body` can take it either shared (parallel) or mutably (pushing)
+    mut pos_q: Query<&'static mut Pos>,
     mut dyno_q: Query<&mut Dyno>,
     mut srx_q: Query<(Entity, &mut StaticRx)>,
     mut stx_q: Query<(Entity, &mut StaticTx)>,
@@ -374,78 +904,203 @@ fn move_interesting_dynos<TriggerRxKind: TriggerKindTrait, TriggerTxKind: Trigge
     // Objects that have a static rx. They may also have a trigger rx.
     // Basically all the stuff we should move in this system
     ents_q: Query<
-        Entity,
+        (Entity, Has<ContinuousCollision>),
         (
             With<Pos>,
             Without<StaticTx>,
             Or<(With<StaticRx>, With<TriggerRxGeneric<TriggerRxKind>>)>,
         ),
     >,
+    pushable_q: Query<&Pushable>,
     // To use and maintain our spatial hashing
     spat_hash_stx: Res<SpatHash<SpatHashStaticTx>>,
     mut spat_hash_ttx_q: Query<&mut SpatKeys<SpatHashTriggerTx>>,
     mut spat_hash_ttx: ResMut<SpatHash<SpatHashTriggerTx>>,
+    tiles: Res<TileColliders>,
 ) {
-    // First do the moving
-    for eid in &ents_q {
-        // Get the data
-        let mut scratch_pos = pos_q.get(eid).expect("No pos on interesting ent").clone();
-        let mut scratch_vel = dyno_q.get(eid).unwrap_or(&Dyno::default()).vel.clone();
-        let srx = srx_q.get(eid).ok();
-        let trx = trx_q.get(eid).ok();
-        debug_assert!(srx.is_some() || trx.is_some());
-        // Inch
-        macro_rules! call_resolve_collisions {
-            () => {{
-                resolve_collisions(
+    // Sorted so that pushing things around always resolves the same way
+    let mut ents = ents_q.iter().collect::<Vec<_>>();
+    ents.sort_by_key(|(eid, _)| *eid);
+    // Where every rx is, so pushes only look at what's nearby.
+    // Nothing to do (and nothing to pay for) if nothing is pushable.
+    let mut rx_index = (!pushable_q.is_empty()).then(|| {
+        carry::RxIndex::new(
+            srx_q
+                .iter()
+                .filter_map(|(eid, srx)| Some((eid, *pos_q.get(eid).ok()?, srx))),
+        )
+    });
+    // Everything that might touch a pushable this step, grown out from the pushables until nothing
+    // else could reach. A body can end up anywhere it sweeps through, pushed out of statics by up
+    // to its own size (a push only has to clear the overlap), or snapped down a slope by up to how
+    // far it moves. Pushables can also get shoved, but never further than everything that could
+    // reach them moves put together.
+    let dt = physics_delta.delta_secs();
+    let mut near_pushables = HashSet::<Entity>::default();
+    // The bounds of every body in `near_pushables`, and whether that body is pushable
+    let mut pushable_areas = Vec::<(FVec2, FVec2, bool)>::new();
+    let mut shove = Fx::ZERO;
+    if rx_index.is_some() {
+        let reaches = ents
+            .iter()
+            .filter_map(|(eid, _)| {
+                let (pos, (_, srx)) = (pos_q.get(*eid).ok()?, srx_q.get(*eid).ok()?);
+                let step = dyno_q.get(*eid).map_or(FVec2::ZERO, |dyno| dyno.vel) * dt;
+                let (mut min, mut max) =
+                    (FVec2::new(Fx::MAX, Fx::MAX), FVec2::new(Fx::MIN, Fx::MIN));
+                for thbox in srx
+                    .get_thboxes(*pos)
+                    .into_iter()
+                    .chain(srx.get_thboxes(*pos + step))
+                {
+                    min = min.min(thbox.bottom_left());
+                    max = max.max(thbox.top_right());
+                }
+                let moves = step.x.abs() + step.y.abs();
+                let pad = FVec2::ONE * ((max - min).x.max((max - min).y) + moves);
+                Some((*eid, min - pad, max + pad, moves))
+            })
+            .collect::<Vec<_>>();
+        let mut joined = vec![false; reaches.len()];
+        loop {
+            let mut grew = false;
+            for (ix, (eid, min, max, moves)) in reaches.iter().enumerate() {
+                if joined[ix] {
+                    continue;
+                }
+                let pushable = pushable_q.contains(*eid);
+                if !pushable && !touches_pushable_area(*min, *max, &pushable_areas, shove) {
+                    continue;
+                }
+                joined[ix] = true;
+                grew = true;
+                near_pushables.insert(*eid);
+                pushable_areas.push((*min, *max, pushable));
+                shove += *moves;
+            }
+            if !grew {
+                break;
+            }
+        }
+    }
+    // Statics only ever collide with txs, and the only txs that move in here are trigger txs on
+    // bodies. So a body is on its own as long as no one can see it move (no ttx), it's nowhere
+    // near anything pushable, and it isn't looking for ttxs that are about to move.
+    let ttxs_moving = ents.iter().any(|(eid, _)| ttx_q.contains(*eid));
+    let is_independent = |eid: Entity| {
+        !ttx_q.contains(eid)
+            && !near_pushables.contains(&eid)
+            && (!ttxs_moving || !trx_q.contains(eid))
+    };
+
+    // First move everything that can go in parallel. Nothing they read changes until they're done.
+    let mut parallel =
+        Parallel::<Vec<(Entity, MovedBody<TriggerRxKind, TriggerTxKind>)>>::default();
+    ents_q.par_iter().for_each(|(eid, continuous)| {
+        if !is_independent(eid) {
+            return;
+        }
+        let mut moved = MovedBody {
+            pos: *pos_q.get(eid).expect("No pos on interesting ent"),
+            vel: dyno_q.get(eid).map_or(FVec2::ZERO, |dyno| dyno.vel),
+            static_colls: vec![],
+            trigger_colls: vec![],
+        };
+        move_body(
+            eid,
+            continuous,
+            &mut moved.pos,
+            &mut moved.vel,
+            srx_q.get(eid).ok(),
+            trx_q.get(eid).ok(),
+            physics_delta.delta_secs(),
+            physics_defaults.max_step,
+            &mut &pos_q,
+            &dyno_q,
+            &stx_q,
+            &ttx_q,
+            &spat_hash_stx,
+            &spat_hash_ttx,
+            &tiles,
+            &mut moved.static_colls,
+            &mut moved.trigger_colls,
+            |_, _, _, _| {},
+        );
+        parallel.borrow_local_mut().push((eid, moved));
+    });
+    let mut parallel = parallel.drain().collect::<HashMap<_, _>>();
+    #[cfg(debug_assertions)]
+    let parallel_eids = parallel.keys().copied().collect::<Vec<_>>();
+
+    // Then go through everyone in order, moving the rest as we get to them
+    for (eid, continuous) in ents {
+        let moved = match parallel.remove(&eid) {
+            Some(moved) => moved,
+            None => {
+                let srx = srx_q.get(eid).ok();
+                let my_mass = pushable_q.get(eid).ok().map(|p| p.get_mass());
+                let mut moved = MovedBody {
+                    pos: *pos_q.get(eid).expect("No pos on interesting ent"),
+                    vel: dyno_q.get(eid).map_or(FVec2::ZERO, |dyno| dyno.vel),
+                    static_colls: vec![],
+                    trigger_colls: vec![],
+                };
+                move_body(
                     eid,
-                    &mut scratch_pos,
-                    &mut scratch_vel,
+                    continuous,
+                    &mut moved.pos,
+                    &mut moved.vel,
                     srx,
-                    trx,
-                    &pos_q,
+                    trx_q.get(eid).ok(),
+                    physics_delta.delta_secs(),
+                    physics_defaults.max_step,
+                    &mut &mut pos_q,
                     &dyno_q,
                     &stx_q,
                     &ttx_q,
-                    &mut static_colls,
-                    &mut trigger_colls,
                     &spat_hash_stx,
                     &spat_hash_ttx,
-                )
-            }};
-        }
-        const DELTA_PER_INCH: Fx = Fx::const_from_int(1);
-        // Resolve collisions once always so stationary objects are still pushed out of each other
-        call_resolve_collisions!();
-        // Inch horizontally
-        let mut amt_moved_hor: Fx = Fx::ZERO;
-        let max_inch_hor = scratch_vel.x.abs() * bullet_time.delta_secs();
-        while amt_moved_hor < max_inch_hor.min(scratch_vel.x.abs()) {
-            let dont_overshoot =
-                (max_inch_hor.min(scratch_vel.x.abs()) - amt_moved_hor).max(Fx::ZERO);
-            let moving_this_step = DELTA_PER_INCH.min(dont_overshoot);
-            amt_moved_hor += moving_this_step;
-            scratch_pos.x += scratch_vel.x.signum() * moving_this_step;
-            call_resolve_collisions!();
-        }
-        // Then inch vertically
-        let mut amt_moved_ver: Fx = Fx::ZERO;
-        let max_inch_ver = scratch_vel.y.abs() * bullet_time.delta_secs();
-        while amt_moved_ver < max_inch_ver.min(scratch_vel.y.abs()) {
-            let dont_overshoot =
-                (max_inch_ver.min(scratch_vel.y.abs()) - amt_moved_ver).max(Fx::ZERO);
-            let moving_this_step = DELTA_PER_INCH.min(dont_overshoot);
-            amt_moved_ver += moving_this_step;
-            scratch_pos.y += scratch_vel.y.signum() * moving_this_step;
-            call_resolve_collisions!();
-        }
-        // NOTE: Why do this (inch horizontally then vertically)? Stops bugs going up and down against wall.
-        // ^read: celeste does this
+                    &tiles,
+                    &mut moved.static_colls,
+                    &mut moved.trigger_colls,
+                    |pos_q, scratch_pos, scratch_vel, static_colls| {
+                        if let (Some((_, srx)), Some(rx_index)) = (srx, rx_index.as_mut()) {
+                            pushable::resolve_pushes(
+                                eid,
+                                scratch_pos,
+                                scratch_vel,
+                                srx,
+                                my_mass,
+                                rx_index,
+                                &pushable_q,
+                                physics_defaults.max_step,
+                                pos_q,
+                                &srx_q,
+                                &stx_q.as_readonly(),
+                                &spat_hash_stx,
+                                &tiles,
+                                static_colls,
+                            );
+                        }
+                    },
+                );
+                moved
+            }
+        };
         // Set the data
         let mut set_pos = pos_q.get_mut(eid).expect("No pos on interesting ent");
-        *set_pos = scratch_pos;
+        *set_pos = moved.pos;
         if let Ok(mut set_dyno) = dyno_q.get_mut(eid) {
-            set_dyno.vel = scratch_vel;
+            set_dyno.vel = moved.vel;
+        }
+        if let (Some(rx_index), Ok((_, srx))) = (rx_index.as_mut(), srx_q.get(eid)) {
+            rx_index.update(eid, moved.pos, srx);
+        }
+        for coll_rec in moved.static_colls {
+            static_colls.insert(coll_rec);
+        }
+        for coll_rec in moved.trigger_colls {
+            trigger_colls.insert(coll_rec);
         }
 
         // Now that we're done moving, we need to update our spatial hashes
@@ -455,10 +1110,25 @@ fn move_interesting_dynos<TriggerRxKind: TriggerKindTrait, TriggerTxKind: Trigge
             (ttx_q.get(eid), spat_hash_ttx_q.get_mut(eid))
         {
             let hboxes = ttx.comps.iter().map(|c| c.hbox.clone()).collect();
-            let new_keys = spat_hash_ttx.update(eid, &spat_keys_ttx, scratch_pos.clone(), hboxes);
+            let new_keys = spat_hash_ttx.update(eid, &spat_keys_ttx, moved.pos, hboxes);
             *spat_keys_ttx = new_keys;
         }
     }
+    // If any body that went in parallel ended up where a pushable could have been, it should have
+    // gone one at a time
+    #[cfg(debug_assertions)]
+    for eid in parallel_eids {
+        let (Ok(pos), Ok((_, srx))) = (pos_q.get(eid), srx_q.get(eid)) else {
+            continue;
+        };
+        for thbox in srx.get_thboxes(*pos) {
+            let (min, max) = (thbox.bottom_left(), thbox.top_right());
+            debug_assert!(
+                !touches_pushable_area(min, max, &pushable_areas, shove),
+                "{eid} moved in parallel but ended up near something pushable"
+            );
+        }
+    }
     // Then update the records in the controls once
     populate_ctrl_coll_keys(
         &mut srx_q,
@@ -470,8 +1140,56 @@ fn move_interesting_dynos<TriggerRxKind: TriggerKindTrait, TriggerTxKind: Trigge
     );
 }
 
-fn update_transforms(mut ents: Query<(&Pos, &mut Transform)>) {
-    for (pos, mut tran) in &mut ents {
+/// Counts down drop-throughs, and stops ignoring specific pass-through txs once we're clear of them
+fn update_pass_through(
+    mut srx_q: Query<(&Pos, &mut StaticRx)>,
+    stx_q: Query<(&Pos, &StaticTx), Without<StaticRx>>,
+    tiles: Res<TileColliders>,
+) {
+    for (pos, mut srx) in &mut srx_q {
+        if srx.pass_through_frames == 0 && srx.pass_through_ignored.is_empty() {
+            continue;
+        }
+        srx.pass_through_frames = srx.pass_through_frames.saturating_sub(1);
+        // Touching counts, otherwise standing on a platform would immediately clear it
+        let slop = fx!(1) / 64;
+        let my_probes = srx
+            .get_thboxes(*pos)
+            .into_iter()
+            .flat_map(|thbox| {
+                [FVec2::X, FVec2::Y, FVec2::new(-1, 0), FVec2::new(0, -1)]
+                    .map(|dir| thbox.translated(dir * slop))
+            })
+            .collect::<Vec<_>>();
+        let my_tiles = tiles.get_overlapping(&my_probes);
+        srx.pass_through_ignored.retain(|tx_eid| {
+            let Ok((tx_pos, stx)) = stx_q.get(*tx_eid) else {
+                return my_tiles.iter().any(|tile| {
+                    tile.ctrl == *tx_eid
+                        && my_probes
+                            .iter()
+                            .any(|my_probe| my_probe.overlaps_with(&tile.thbox))
+                });
+            };
+            stx.get_thboxes(*tx_pos).iter().any(|tx_thbox| {
+                my_probes
+                    .iter()
+                    .any(|my_probe| my_probe.overlaps_with(tx_thbox))
+            })
+        });
+    }
+}
+
+fn update_transforms(
+    mut ents: Query<(&Pos, Option<(&RelPos, &ChildOf)>, &mut Transform)>,
+    parent_q: Query<&Pos>,
+) {
+    for (pos, rel_pos, mut tran) in &mut ents {
+        if let Some((rel_pos, child_of)) = rel_pos {
+            rel_pos.write_transform(pos, parent_q.get(child_of.parent()).ok(), &mut tran);
+            tran.translation.z = pos.z.to_num();
+            continue;
+        }
         tran.translation.x = pos.x.round().to_num::<f32>();
         tran.translation.y = pos.y.round().to_num::<f32>();
         tran.translation.z = pos.z.to_num();
@@ -480,21 +1198,290 @@ fn update_transforms(mut ents: Query<(&Pos, &mut Transform)>) {
 
 pub(super) fn register_logic<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
     app: &mut App,
+    fixed_timestep: bool,
 ) {
-    app.add_systems(
-        Update,
-        (
-            reset_colls_every_frame::<TriggerRxKind, TriggerTxKind>,
-            move_uninteresting_dynos::<TriggerRxKind, TriggerTxKind>,
-            move_static_txs::<TriggerTxKind>,
-            move_interesting_dynos::<TriggerRxKind, TriggerTxKind>,
-            update_transforms,
-        )
-            .chain()
-            .in_set(PhysicsSet),
-    );
+    app.insert_resource(PhysicsDelta::default());
+    let step = (
+        timestep::update_physics_delta,
+        dyno::apply_dyno_bodies,
+        medium::apply_mediums::<TriggerRxKind, TriggerTxKind>,
+        controller::update_platformer_controllers,
+        reset_colls_every_frame::<TriggerRxKind, TriggerTxKind>,
+        move_uninteresting_dynos::<TriggerRxKind, TriggerTxKind>,
+        move_static_txs::<TriggerTxKind>,
+        pos::resolve_rel_pos::<TriggerTxKind>,
+        move_interesting_dynos::<TriggerRxKind, TriggerTxKind>,
+        pos::resolve_rel_pos::<TriggerTxKind>,
+        update_pass_through,
+        contacts::update_contacts,
+        hit_events::trigger_hits::<TriggerRxKind, TriggerTxKind>,
+        update_trigger_pairs::<TriggerRxKind, TriggerTxKind>,
+        snapshot::record_physics_history::<TriggerRxKind, TriggerTxKind>,
+    )
+        .chain();
+    if fixed_timestep {
+        // NOTE: A frame can run zero or several ticks, so colls are whatever the LAST tick left behind
+        app.add_systems(
+            FixedUpdate,
+            (timestep::store_prev_pos, step).chain().in_set(PhysicsSet),
+        );
+        app.add_systems(Update, timestep::interpolate_transforms.in_set(PhysicsSet));
+    } else {
+        app.add_systems(Update, (step, update_transforms).chain().in_set(PhysicsSet));
+    }
     #[cfg(debug_assertions)]
     {
         app.add_systems(Update, invariants);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{
+        colls::CollKey,
+        hbox::HBoxCorner,
+        test_utils::{step, test_app, TestSettings, TestTriggerRx, TestTriggerTx},
+    };
+
+    fn spawn_tx<X: fixed::traits::ToFixed, Y: fixed::traits::ToFixed>(
+        app: &mut App,
+        x: X,
+        y: Y,
+        kind: StaticTxKind,
+        hbox: HBox,
+    ) -> Entity {
+        app.world_mut()
+            .spawn((Pos::new(x, y), StaticTx::single(kind, hbox)))
+            .id()
+    }
+
+    fn spawn_rx(app: &mut App, x: i32, y: i32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(x, y),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn moving_tx_carries_riders_and_pushes_whats_in_the_way() {
+        let mut app = test_app(default());
+        let platform = app
+            .world_mut()
+            .spawn((
+                Pos::new(0, 0),
+                Dyno::new(fx!(64), Fx::ZERO),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(20, 4)),
+            ))
+            .id();
+        let rider = spawn_rx(&mut app, 0, 4);
+        let pushed = spawn_rx(&mut app, 14, 0);
+        let bystander = spawn_rx(&mut app, 100, 100);
+        step(&mut app, 10);
+
+        let pos_of = |app: &App, eid: Entity| *app.world().get::<Pos>(eid).unwrap();
+        assert_eq!(pos_of(&app, platform).as_fvec2(), FVec2::new(10, 0));
+        assert_eq!(pos_of(&app, rider).as_fvec2(), FVec2::new(10, 4));
+        assert_eq!(pos_of(&app, pushed).as_fvec2(), FVec2::new(22, 0));
+        assert_eq!(pos_of(&app, bystander).as_fvec2(), FVec2::new(100, 100));
+    }
+
+    #[test]
+    fn diagonal_tx_carries_riders_up_and_across() {
+        let mut app = test_app(TestSettings::default().with_gravity(512));
+        let platform = app
+            .world_mut()
+            .spawn((
+                Pos::new(0, 0),
+                Dyno::new(fx!(64), fx!(64)),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(20, 4)),
+            ))
+            .id();
+        let rider = spawn_falling_rx(&mut app, 0, 4);
+        let pushed = spawn_rx(&mut app, 14, 0);
+        step(&mut app, 10);
+
+        let pos_of = |app: &App, eid: Entity| *app.world().get::<Pos>(eid).unwrap();
+        assert_eq!(pos_of(&app, platform).as_fvec2(), FVec2::new(10, 10));
+        // Bounced up a little by the rise each step, but never left behind or shoved off
+        let rider_pos = pos_of(&app, rider);
+        assert_eq!(rider_pos.x, fx!(10));
+        assert!(rider_pos.y >= fx!(14) && rider_pos.y < fx!(15));
+        // In the way until the platform rose past it
+        assert_eq!(pos_of(&app, pushed).as_fvec2(), FVec2::new(15, 0));
+    }
+
+    #[test]
+    fn pass_up_catches_bodies_that_sink_up_to_max_step() {
+        // 4 pixels a step with a max step of 4, so the body ends up 2.5 pixels into the platform
+        let mut app = test_app(TestSettings::default().with_max_step(4));
+        spawn_tx(&mut app, 0, -2, StaticTxKind::PassUp, HBox::new(20, 4));
+        let body = app
+            .world_mut()
+            .spawn((
+                Pos::new(0, fx!(15.5)),
+                Dyno::new(Fx::ZERO, fx!(-256)),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id();
+        step(&mut app, 6);
+        assert_eq!(app.world().get::<Pos>(body).unwrap().y, fx!(2));
+        assert_eq!(app.world().get::<Dyno>(body).unwrap().vel, FVec2::ZERO);
+    }
+
+    fn spawn_falling_rx(app: &mut App, x: i32, y: i32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(x, y),
+                dyno::DynoBody::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn pass_up_only_stops_things_from_above() {
+        let mut app = test_app(default());
+        spawn_tx(&mut app, 0, 0, StaticTxKind::PassUp, HBox::new(20, 4));
+        let from_below = app
+            .world_mut()
+            .spawn((
+                Pos::new(0, -10),
+                Dyno::new(Fx::ZERO, fx!(64)),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id();
+        step(&mut app, 20);
+        assert_eq!(app.world().get::<Pos>(from_below).unwrap().y, fx!(10));
+    }
+
+    #[test]
+    fn dropping_and_ignoring_pass_throughs() {
+        let mut app = test_app(TestSettings::default().with_gravity(512));
+        let left = spawn_tx(&mut app, 0, 0, StaticTxKind::PassUp, HBox::new(20, 4));
+        let right = spawn_tx(&mut app, 100, 0, StaticTxKind::PassUp, HBox::new(20, 4));
+        let dropper = spawn_falling_rx(&mut app, 0, 4);
+        let ignorer = spawn_falling_rx(&mut app, 100, 4);
+        let stayer = spawn_falling_rx(&mut app, 5, 4);
+        step(&mut app, 10);
+        let y_of = |app: &App, eid: Entity| app.world().get::<Pos>(eid).unwrap().y;
+        for eid in [dropper, ignorer, stayer] {
+            assert_eq!(y_of(&app, eid), fx!(4));
+        }
+
+        app.world_mut()
+            .get_mut::<StaticRx>(dropper)
+            .unwrap()
+            .drop_through(60);
+        app.world_mut()
+            .get_mut::<StaticRx>(ignorer)
+            .unwrap()
+            .ignore_pass_through(right);
+        step(&mut app, 60);
+        assert!(y_of(&app, dropper) < fx!(-4));
+        assert!(y_of(&app, ignorer) < fx!(-4));
+        assert_eq!(y_of(&app, stayer), fx!(4));
+
+        // Done ignoring once they're clear
+        let srx_of = |eid: Entity| app.world().get::<StaticRx>(eid).unwrap();
+        assert!(!srx_of(dropper).is_ignoring_pass_through(left));
+        assert!(!srx_of(ignorer).is_ignoring_pass_through(right));
+    }
+
+    #[test]
+    fn walking_down_slopes_sticks_unless_dropping_through() {
+        let mut app = test_app(default());
+        let walker_on = |app: &mut App, x: i32, kind: StaticTxKind| {
+            spawn_tx(app, x, 0, kind, HBox::ramp_22(16, HBoxCorner::BottomLeft));
+            // Standing on the slope, which goes down to the right
+            app.world_mut()
+                .spawn((
+                    Pos::new(x - 8, 7),
+                    Dyno::new(fx!(64), Fx::ZERO),
+                    StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+                ))
+                .id()
+        };
+        let on_solid = walker_on(&mut app, 0, StaticTxKind::Solid);
+        let on_pass_up = walker_on(&mut app, 100, StaticTxKind::PassUp);
+        let dropping = walker_on(&mut app, 200, StaticTxKind::PassUp);
+        app.world_mut()
+            .get_mut::<StaticRx>(dropping)
+            .unwrap()
+            .drop_through(60);
+        step(&mut app, 10);
+
+        let pos_of = |eid: Entity| app.world().get::<Pos>(eid).unwrap().as_fvec2();
+        assert_eq!(pos_of(on_solid), FVec2::new(2, 2));
+        assert_eq!(pos_of(on_pass_up), FVec2::new(102, 2));
+        assert_eq!(pos_of(dropping), FVec2::new(202, 7));
+    }
+
+    #[test]
+    fn parallel_moves_come_out_the_same_every_time() {
+        type Frame = Vec<(FVec2, FVec2, Vec<CollKey>, Vec<(FVec2, Entity)>)>;
+        fn run() -> Vec<Frame> {
+            let mut app = test_app(TestSettings::default().with_gravity(512));
+            spawn_tx(&mut app, 0, -4, StaticTxKind::Solid, HBox::new(400, 8));
+            app.world_mut().spawn((
+                Pos::new(0, 40),
+                Dyno::new(fx!(32), Fx::ZERO),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(40, 4)),
+            ));
+            app.world_mut().spawn((
+                Pos::new(-100, 20),
+                Dyno::new(fx!(64), Fx::ZERO),
+                TriggerTxGeneric::single(TestTriggerTx::Zone, HBox::new(16, 16)),
+            ));
+            // A mix of plain bodies (moved in parallel), pushables and trigger rxs (moved in order)
+            let bodies = (0..40)
+                .map(|ix| {
+                    let mut ent = app.world_mut().spawn((
+                        Pos::new(ix * 9 - 180, 10 + (ix % 5) * 12),
+                        Dyno::new(fx!((ix % 7) * 16 - 48), Fx::ZERO),
+                        dyno::DynoBody::default(),
+                        StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+                    ));
+                    if ix % 6 == 0 {
+                        ent.insert(Pushable::new(1 + ix % 3));
+                    }
+                    if ix % 10 == 3 {
+                        ent.insert(TriggerRxGeneric::single(
+                            TestTriggerRx::Body,
+                            HBox::new(4, 4),
+                        ));
+                    }
+                    ent.id()
+                })
+                .collect::<Vec<_>>();
+            (0..60)
+                .map(|_| {
+                    step(&mut app, 1);
+                    let world = app.world();
+                    let static_colls = world.resource::<StaticColls>();
+                    bodies
+                        .iter()
+                        .map(|eid| {
+                            let srx = world.get::<StaticRx>(*eid).unwrap();
+                            (
+                                world.get::<Pos>(*eid).unwrap().as_fvec2(),
+                                world.get::<Dyno>(*eid).unwrap().vel,
+                                srx.coll_keys.clone(),
+                                static_colls
+                                    .iter_refs(&srx.coll_keys)
+                                    .map(|coll| (coll.push, coll.tx_ctrl))
+                                    .collect(),
+                            )
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+        let first = run();
+        for _ in 0..3 {
+            assert!(first == run(), "Parallel moves diverged between runs");
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Volumes of stuff that bodies move through: water, wind tunnels, low-gravity zones, etc.
+//! A `Medium` goes next to a `TriggerTx`. Any `Dyno` whose `TriggerRx` overlaps it gets pushed
+//! around every step, scaled by how much of the rx hbox is inside.
+//! NOTE: Like grounding, this uses the trigger colls from the end of the last step.
+
+use bevy::prelude::*;
+use fixed::traits::ToFixed;
+
+use crate::{
+    fx,
+    glue::{fvec::FVec2, Fx},
+    physics::{
+        colls::TriggerCollsGeneric,
+        dyno::{Dyno, DynoBody},
+        plugin::PhysicsDefaults,
+        timestep::PhysicsDelta,
+        triggers::{TriggerKindTrait, TriggerRxGeneric, TriggerTxGeneric},
+    },
+    prelude::HashMap,
+};
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct Medium {
+    /// Upward acceleration (pixels/sec²) when fully inside
+    pub buoyancy: Fx,
+    /// Fraction of velocity lost per second when fully inside
+    pub drag: Fx,
+    /// Replaces the gravity from `PhysicsSettingsGeneric` for `DynoBody`s (still uses their scale)
+    pub gravity: Option<Fx>,
+    /// The velocity things drift towards
+    pub flow: FVec2,
+    /// How fast things catch up to `flow`, as a fraction per second when fully inside
+    pub flow_rate: Fx,
+}
+impl Medium {
+    pub fn with_buoyancy<B: ToFixed>(mut self, buoyancy: B) -> Self {
+        self.buoyancy = fx!(buoyancy);
+        self
+    }
+    pub fn with_drag<D: ToFixed>(mut self, drag: D) -> Self {
+        self.drag = fx!(drag);
+        self
+    }
+    pub fn with_gravity<G: ToFixed>(mut self, gravity: G) -> Self {
+        self.gravity = Some(fx!(gravity));
+        self
+    }
+    pub fn with_flow<X: ToFixed, Y: ToFixed, R: ToFixed>(mut self, x: X, y: Y, rate: R) -> Self {
+        self.flow = FVec2::new(x, y);
+        self.flow_rate = fx!(rate);
+        self
+    }
+}
+
+/// A dyno started overlapping a medium this step
+#[derive(Event, Message, Clone, Debug)]
+pub struct MediumEnter {
+    pub ctrl: Entity,
+    pub medium: Entity,
+}
+
+/// A dyno that was overlapping a medium isn't anymore. Either may have been despawned already.
+#[derive(Event, Message, Clone, Debug)]
+pub struct MediumExit {
+    pub ctrl: Entity,
+    pub medium: Entity,
+}
+
+/// Which dynos are in which mediums, and how deep, as of the last step
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MediumOverlaps {
+    map: HashMap<(Entity, Entity), Fx>,
+}
+impl MediumOverlaps {
+    /// How much (0 to 1) of this ctrl is inside this medium
+    pub fn get_fraction(&self, ctrl: Entity, medium: Entity) -> Fx {
+        self.map.get(&(ctrl, medium)).copied().unwrap_or(Fx::ZERO)
+    }
+    /// The mediums this ctrl is in, with how much of it is inside each
+    pub fn get_mediums(&self, ctrl: Entity) -> Vec<(Entity, Fx)> {
+        let mut result = self
+            .map
+            .iter()
+            .filter(|((other, _), _)| *other == ctrl)
+            .map(|((_, medium), fraction)| (*medium, *fraction))
+            .collect::<Vec<_>>();
+        result.sort_by_key(|(medium, _)| *medium);
+        result
+    }
+}
+
+pub(super) fn apply_mediums<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
+    physics_delta: Res<PhysicsDelta>,
+    physics_defaults: Res<PhysicsDefaults>,
+    trigger_colls: Res<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut pairs: ResMut<MediumOverlaps>,
+    mediums_q: Query<&Medium>,
+    trx_q: Query<&TriggerRxGeneric<TriggerRxKind>>,
+    ttx_q: Query<&TriggerTxGeneric<TriggerTxKind>>,
+    mut dynos_q: Query<(&mut Dyno, Option<&DynoBody>)>,
+    mut commands: Commands,
+    mut enter_writer: MessageWriter<MediumEnter>,
+    mut exit_writer: MessageWriter<MediumExit>,
+) {
+    // With several rx hboxes in the same medium, the most submerged one counts
+    let mut this_step = HashMap::<(Entity, Entity), Fx>::new();
+    for coll in trigger_colls.map.values() {
+        if !mediums_q.contains(coll.tx_ctrl) || !dynos_q.contains(coll.rx_ctrl) {
+            continue;
+        }
+        let (Ok(trx), Ok(ttx)) = (trx_q.get(coll.rx_ctrl), ttx_q.get(coll.tx_ctrl)) else {
+            continue;
+        };
+        let rx_hboxes = trx
+            .comps
+            .iter()
+            .filter(|comp| comp.hbox.get_marker() == coll.rx_hbox);
+        for rx_comp in rx_hboxes {
+            let size = rx_comp.hbox.get_size();
+            let area = Fx::from_num(size.x * size.y);
+            if area == Fx::ZERO {
+                continue;
+            }
+            let rx_thbox = rx_comp.hbox.translated(coll.rx_pos.as_fvec2());
+            for tx_comp in ttx
+                .comps
+                .iter()
+                .filter(|comp| comp.hbox.get_marker() == coll.tx_hbox)
+            {
+                let tx_thbox = tx_comp.hbox.translated(coll.tx_pos.as_fvec2());
+                if !rx_thbox.overlaps_with(&tx_thbox) {
+                    continue;
+                }
+                let fraction = (rx_thbox.area_intersecting(&tx_thbox) / area).min(Fx::ONE);
+                let entry = this_step
+                    .entry((coll.rx_ctrl, coll.tx_ctrl))
+                    .or_insert(Fx::ZERO);
+                *entry = (*entry).max(fraction);
+            }
+        }
+    }
+
+    // Sorted so the effects and events happen in the same order every time
+    let mut current = this_step.iter().collect::<Vec<_>>();
+    current.sort_by_key(|(key, _)| **key);
+    let dt = physics_delta.delta_secs();
+    for ((ctrl, medium_eid), fraction) in current {
+        let (Ok(medium), Ok((mut dyno, body))) =
+            (mediums_q.get(*medium_eid), dynos_q.get_mut(*ctrl))
+        else {
+            continue;
+        };
+        let scale = *fraction * dt;
+        let mut vel = dyno.vel;
+        vel.y += medium.buoyancy * scale;
+        if let (Some(gravity), Some(body)) = (medium.gravity, body) {
+            // Take back the normal gravity that was just applied, and add ours instead
+            vel.y += (physics_defaults.gravity - gravity) * body.gravity_scale * scale;
+        }
+        if medium.drag != Fx::ZERO {
+            vel *= (Fx::ONE - medium.drag * scale).max(Fx::ZERO);
+        }
+        if medium.flow_rate != Fx::ZERO {
+            vel += (medium.flow - vel) * (medium.flow_rate * scale).min(Fx::ONE);
+        }
+        dyno.vel = vel;
+
+        if !pairs.map.contains_key(&(*ctrl, *medium_eid)) {
+            let event = MediumEnter {
+                ctrl: *ctrl,
+                medium: *medium_eid,
+            };
+            enter_writer.write(event.clone());
+            commands.trigger(event);
+        }
+    }
+
+    let mut exited = pairs
+        .map
+        .keys()
+        .filter(|key| !this_step.contains_key(*key))
+        .copied()
+        .collect::<Vec<_>>();
+    exited.sort();
+    for (ctrl, medium) in exited {
+        let event = MediumExit { ctrl, medium };
+        exit_writer.write(event.clone());
+        commands.trigger(event);
+    }
+
+    pairs.map = this_step;
+}
+
+pub(super) fn register_mediums(app: &mut App) {
+    app.insert_resource(MediumOverlaps::default());
+    app.add_message::<MediumEnter>();
+    app.add_message::<MediumExit>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{
+        hbox::HBox,
+        pos::Pos,
+        test_utils::{step, test_app, TestSettings, TestTriggerRx, TestTriggerTx},
+    };
+
+    #[derive(Resource, Default)]
+    struct Crossings {
+        entered: Vec<(Entity, Entity)>,
+        exited: Vec<(Entity, Entity)>,
+    }
+
+    fn medium_app(settings: TestSettings) -> App {
+        let mut app = test_app(settings);
+        app.init_resource::<Crossings>();
+        app.add_observer(
+            |trigger: On<MediumEnter>, mut crossings: ResMut<Crossings>| {
+                let event = trigger.event();
+                crossings.entered.push((event.ctrl, event.medium));
+            },
+        );
+        app.add_observer(
+            |trigger: On<MediumExit>, mut crossings: ResMut<Crossings>| {
+                let event = trigger.event();
+                crossings.exited.push((event.ctrl, event.medium));
+            },
+        );
+        app
+    }
+
+    fn spawn_medium(app: &mut App, medium: Medium) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(0, 0),
+                TriggerTxGeneric::single(TestTriggerTx::Zone, HBox::new(32, 32)),
+                medium,
+            ))
+            .id()
+    }
+
+    fn spawn_body(app: &mut App, x: i32, y: i32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(x, y),
+                Dyno::default(),
+                DynoBody::default(),
+                TriggerRxGeneric::single(TestTriggerRx::Body, HBox::new(4, 4)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn flow_carries_things_out_the_other_side() {
+        let mut app = medium_app(default());
+        // Catches up to the flow in a single step
+        let water = spawn_medium(&mut app, Medium::default().with_flow(64, 0, 64));
+        let body = spawn_body(&mut app, 0, 0);
+        step(&mut app, 2);
+        assert_eq!(
+            app.world().get::<Dyno>(body).unwrap().vel,
+            FVec2::new(64, 0)
+        );
+        assert_eq!(
+            app.world().resource::<MediumOverlaps>().get_mediums(body),
+            vec![(water, Fx::ONE)]
+        );
+        assert_eq!(
+            app.world().resource::<Crossings>().entered,
+            vec![(body, water)]
+        );
+
+        // A pixel a step, so it's out after about 16
+        step(&mut app, 30);
+        assert_eq!(
+            app.world().get::<Dyno>(body).unwrap().vel,
+            FVec2::new(64, 0)
+        );
+        assert!(app
+            .world()
+            .resource::<MediumOverlaps>()
+            .get_mediums(body)
+            .is_empty());
+        let crossings = app.world().resource::<Crossings>();
+        assert_eq!(crossings.entered, vec![(body, water)]);
+        assert_eq!(crossings.exited, vec![(body, water)]);
+    }
+
+    #[test]
+    fn partly_inside_gets_part_of_the_effect() {
+        let mut app = medium_app(TestSettings::default().with_gravity(512));
+        // No gravity, and enough buoyancy to push a fully submerged body up 64 px/s every second
+        let water = spawn_medium(
+            &mut app,
+            Medium::default().with_gravity(0).with_buoyancy(64),
+        );
+        let deep = spawn_body(&mut app, 0, 0);
+        // Half of it sticks out the left side
+        let shallow = spawn_body(&mut app, -16, 0);
+        step(&mut app, 1);
+        let vel_of = |app: &App, eid: Entity| app.world().get::<Dyno>(eid).unwrap().vel.y;
+        let before = (vel_of(&app, deep), vel_of(&app, shallow));
+        step(&mut app, 1);
+
+        let overlaps = app.world().resource::<MediumOverlaps>();
+        assert_eq!(overlaps.get_fraction(deep, water), Fx::ONE);
+        assert_eq!(overlaps.get_fraction(shallow, water), fx!(0.5));
+        // Gravity is swapped for the medium's in proportion, so all that's left is buoyancy
+        let gravity = fx!(512) / 64;
+        assert_eq!(vel_of(&app, deep) - before.0, Fx::ONE);
+        assert_eq!(
+            vel_of(&app, shallow) - before.1,
+            (-gravity + gravity / 2) + fx!(0.5)
+        );
+    }
+}
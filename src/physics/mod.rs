@@ -1,14 +1,30 @@
 use bevy::prelude::*;
 
+mod anim_hboxes;
+mod carry;
 mod colls;
+mod contacts;
+mod controller;
 mod debug;
 mod dyno;
 mod hbox;
+mod hit_events;
+mod layers;
 mod logic;
+mod medium;
 mod plugin;
 mod pos;
+mod pushable;
+mod query;
+mod snapshot;
 mod spat_hash;
 mod statics;
+mod teleport;
+#[cfg(test)]
+pub(crate) mod test_utils;
+mod tiles;
+mod timestep;
+mod trigger_events;
 mod triggers;
 
 /// The set that contains all physics related systems
@@ -16,16 +32,33 @@ mod triggers;
 pub(crate) struct PhysicsSet;
 
 pub mod prelude {
+    pub use super::carry::StaticCrush;
     pub use super::colls::{
         ByHBox, StaticCollRec, StaticColls, TriggerCollRecGeneric, TriggerCollsGeneric,
     };
-    pub use super::dyno::Dyno;
-    pub use super::hbox::{HBox, HBoxMarker};
+    pub use super::contacts::{Contact, ContactSide, Contacts};
+    pub use super::controller::PlatformerController;
+    pub use super::dyno::{ContinuousCollision, Dyno, DynoBody};
+    pub use super::hbox::{HBox, HBoxCorner, HBoxMarker, HBoxShape};
+    pub use super::hit_events::{ObserveHits, StaticHit, TriggerHitGeneric};
+    pub use super::layers::PhysicsLayers;
+    pub use super::medium::{Medium, MediumEnter, MediumExit, MediumOverlaps};
     pub use super::plugin::*;
-    pub use super::pos::Pos;
+    pub use super::pos::{Pos, RelPos};
+    pub use super::pushable::Pushable;
+    pub use super::query::{PhysicsQueryGeneric, StaticCastHit, TriggerCastHit};
+    pub use super::snapshot::{
+        PhysicsHistoryGeneric, PhysicsSnapshotGeneric, RestorePhysicsGeneric, RewindPhysics,
+    };
     pub(crate) use super::spat_hash::{
         on_remove_spat_hash, SpatHash, SpatHashOccludeLight, SpatKeys,
     };
-    pub use super::statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind};
-    pub use super::triggers::{TriggerKindTrait, TriggerRxGeneric, TriggerTxGeneric};
+    pub use super::spat_hash::{Broadphase, BroadphaseKindStats, BroadphaseStats};
+    pub use super::statics::{StaticRx, StaticRxKind, StaticTx, StaticTxHBoxes, StaticTxKind};
+    pub use super::teleport::{Teleport, TeleportFailed};
+    pub use super::tiles::{TileColliders, TileGrid, TileGridCtrl};
+    pub use super::trigger_events::{TriggerEnterGeneric, TriggerExitGeneric, TriggerStayGeneric};
+    pub use super::triggers::{
+        TriggerKindTrait, TriggerRxGeneric, TriggerTxGeneric, TriggerTxHBoxesGeneric,
+    };
 }
@@ -1,11 +1,29 @@
 use bevy::prelude::*;
+use fixed::traits::ToFixed;
 
-use crate::physics::{colls, logic, triggers::TriggerKindTrait};
+use crate::{
+    fx,
+    glue::Fx,
+    physics::{
+        anim_hboxes, colls, logic, medium, snapshot, teleport, tiles, trigger_events,
+        triggers::TriggerKindTrait,
+    },
+};
 
-use super::spat_hash;
+use super::spat_hash::{self, Broadphase};
 
 pub struct PhysicsSettingsGeneric<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
 {
+    /// The biggest step (in pixels) things take when inching along. Smaller is more precise, bigger is faster.
+    /// Things thinner than this can be tunneled through, unless the mover has `ContinuousCollision`.
+    pub(crate) max_step: Fx,
+    /// If set, physics runs in `FixedUpdate` at this many ticks per second instead of once per frame
+    pub(crate) fixed_hz: Option<u32>,
+    /// Downward acceleration (pixels/sec²) applied to anything with a `DynoBody`
+    pub(crate) gravity: Fx,
+    pub(crate) static_broadphase: Broadphase,
+    pub(crate) trigger_broadphase: Broadphase,
+    pub(crate) light_broadphase: Broadphase,
     _pd: std::marker::PhantomData<(TriggerRxKind, TriggerTxKind)>,
 }
 impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> Default
@@ -13,33 +31,120 @@ impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> Default
 {
     fn default() -> Self {
         Self {
+            max_step: Fx::ONE,
+            fixed_hz: None,
+            gravity: Fx::ZERO,
+            static_broadphase: default(),
+            trigger_broadphase: default(),
+            light_broadphase: default(),
             _pd: std::marker::PhantomData,
         }
     }
 }
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> Clone
+    for PhysicsSettingsGeneric<TriggerRxKind, TriggerTxKind>
+{
+    fn clone(&self) -> Self {
+        Self {
+            max_step: self.max_step,
+            fixed_hz: self.fixed_hz,
+            gravity: self.gravity,
+            static_broadphase: self.static_broadphase,
+            trigger_broadphase: self.trigger_broadphase,
+            light_broadphase: self.light_broadphase,
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
+    PhysicsSettingsGeneric<TriggerRxKind, TriggerTxKind>
+{
+    /// Panics if `max_step` isn't positive (nothing would ever finish inching)
+    pub fn with_max_step<S: ToFixed>(mut self, max_step: S) -> Self {
+        self.max_step = fx!(max_step);
+        assert!(self.max_step > Fx::ZERO, "max_step must be positive");
+        self
+    }
+    /// Runs physics at a fixed tick rate, interpolating what gets rendered in between.
+    /// Makes the simulation independent of the display's refresh rate.
+    /// Panics if `hz` is zero.
+    pub fn with_fixed_timestep(mut self, hz: u32) -> Self {
+        assert!(hz > 0, "fixed timestep hz must be positive");
+        self.fixed_hz = Some(hz);
+        self
+    }
+    pub fn with_gravity<G: ToFixed>(mut self, gravity: G) -> Self {
+        self.gravity = fx!(gravity);
+        self
+    }
+    pub fn is_fixed_timestep(&self) -> bool {
+        self.fixed_hz.is_some()
+    }
+    /// How to find candidate `StaticTx`s. Big solids like `SweepAndPrune` or big grid cells.
+    pub fn with_static_broadphase(mut self, broadphase: Broadphase) -> Self {
+        self.static_broadphase = broadphase;
+        self
+    }
+    /// How to find candidate `TriggerTx`s
+    pub fn with_trigger_broadphase(mut self, broadphase: Broadphase) -> Self {
+        self.trigger_broadphase = broadphase;
+        self
+    }
+    /// How lights find the things that might occlude them
+    pub fn with_light_broadphase(mut self, broadphase: Broadphase) -> Self {
+        self.light_broadphase = broadphase;
+        self
+    }
+}
+
+/// The non-generic parts of the settings, for systems to read
+#[derive(Resource, Clone, Debug)]
+pub(crate) struct PhysicsDefaults {
+    pub(crate) max_step: Fx,
+    pub(crate) fixed_hz: Option<u32>,
+    pub(crate) gravity: Fx,
+}
 
 pub(crate) struct PhysicsPluginGeneric<
     TriggerRxKind: TriggerKindTrait,
     TriggerTxKind: TriggerKindTrait,
 > {
-    _pd: std::marker::PhantomData<(TriggerRxKind, TriggerTxKind)>,
+    settings: PhysicsSettingsGeneric<TriggerRxKind, TriggerTxKind>,
 }
-impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> Default
-    for PhysicsPluginGeneric<TriggerRxKind, TriggerTxKind>
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
+    PhysicsPluginGeneric<TriggerRxKind, TriggerTxKind>
 {
-    fn default() -> Self {
-        Self {
-            _pd: std::marker::PhantomData,
-        }
+    pub fn new(settings: PhysicsSettingsGeneric<TriggerRxKind, TriggerTxKind>) -> Self {
+        Self { settings }
     }
 }
 impl<TriggerRx: TriggerKindTrait, TriggerTx: TriggerKindTrait> Plugin
     for PhysicsPluginGeneric<TriggerRx, TriggerTx>
 {
     fn build(&self, app: &mut App) {
+        app.insert_resource(PhysicsDefaults {
+            max_step: self.settings.max_step,
+            fixed_hz: self.settings.fixed_hz,
+            gravity: self.settings.gravity,
+        });
+        if let Some(hz) = self.settings.fixed_hz {
+            app.insert_resource(Time::<Fixed>::from_hz(hz as f64));
+        }
+
+        anim_hboxes::register_anim_hboxes::<TriggerRx, TriggerTx>(app);
         colls::register_colls::<TriggerRx, TriggerTx>(app);
-        logic::register_logic::<TriggerRx, TriggerTx>(app);
-        spat_hash::register_spat_hash(app);
+        logic::register_logic::<TriggerRx, TriggerTx>(app, self.settings.fixed_hz.is_some());
+        medium::register_mediums(app);
+        spat_hash::register_spat_hash(
+            app,
+            self.settings.static_broadphase,
+            self.settings.trigger_broadphase,
+            self.settings.light_broadphase,
+        );
+        trigger_events::register_trigger_events::<TriggerRx, TriggerTx>(app);
+        snapshot::register_snapshot::<TriggerRx, TriggerTx>(app);
+        teleport::register_teleport::<TriggerTx>(app);
+        tiles::register_tiles(app);
 
         #[cfg(debug_assertions)]
         {
@@ -50,3 +155,20 @@ impl<TriggerRx: TriggerKindTrait, TriggerTx: TriggerKindTrait> Plugin
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::physics::test_utils::TestSettings;
+
+    #[test]
+    #[should_panic(expected = "max_step must be positive")]
+    fn zero_max_step_is_rejected() {
+        let _ = TestSettings::default().with_max_step(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "hz must be positive")]
+    fn zero_hz_is_rejected() {
+        let _ = TestSettings::default().with_fixed_timestep(0);
+    }
+}
@@ -6,7 +6,16 @@ use fixed::traits::ToFixed;
 
 use crate::{
     fx,
-    glue::{fvec::FVec2, Fx},
+    glue::{
+        fvec::FVec2,
+        terp::{TerpMode, Terpable},
+        Fx,
+    },
+    physics::{
+        spat_hash::{SpatHash, SpatHashStaticTx, SpatHashTriggerTx, SpatKeys},
+        statics::StaticTx,
+        triggers::{TriggerKindTrait, TriggerTxGeneric},
+    },
 };
 
 #[derive(Copy, Clone, Debug, Default, Component)]
@@ -21,6 +30,10 @@ fn on_add_pos(mut world: bevy::ecs::world::DeferredWorld, hook: HookContext) {
     let me = *world
         .get::<Pos>(hook.entity)
         .expect("Couldn't get Pos after add");
+    // The transform of a relative thing is relative too, `RelPos` handles it
+    if world.get::<RelPos>(hook.entity).is_some() {
+        return;
+    }
     match world.get_mut::<Transform>(hook.entity) {
         Some(mut tran) => {
             tran.translation.x = me.x.round().to_num();
@@ -103,8 +116,196 @@ impl std::ops::Neg for Pos {
         self
     }
 }
+/// Makes `Pos` follow the parent's `Pos`, plus an offset. Put it on a child (`ChildOf`) for things
+/// like held weapon hitboxes, or a light on a moving enemy.
+/// Resolved during physics before and after the interesting stuff moves, so txs are where their
+/// owner is by the time anything collides with them. Nests (the parent can also be relative).
+/// NOTE: `Pos` is still absolute and gets overwritten, so set `offset` to move these around.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[component(on_add = on_add_rel_pos)]
+#[require(Pos)]
+pub struct RelPos {
+    pub offset: FVec2,
+}
+fn on_add_rel_pos(mut world: bevy::ecs::world::DeferredWorld, hook: HookContext) {
+    let me = *world
+        .get::<RelPos>(hook.entity)
+        .expect("Couldn't get RelPos after add");
+    // Not resolved yet, so there's no `Pos` to go off of
+    if let Some(mut tran) = world.get_mut::<Transform>(hook.entity) {
+        me.write_transform(&Pos::default(), None, &mut tran);
+    }
+}
+impl RelPos {
+    pub fn new<X: ToFixed, Y: ToFixed>(x: X, y: Y) -> Self {
+        Self {
+            offset: FVec2::new(x, y),
+        }
+    }
+    /// Bevy already puts children relative to their parent, so the transform is how far apart the
+    /// two are once they're both rounded. Rounding just the offset can land a pixel off from `Pos`.
+    /// Until the parent's `Pos` is known, the rounded offset is the best we've got.
+    pub(crate) fn write_transform(
+        &self,
+        pos: &Pos,
+        parent_pos: Option<&Pos>,
+        tran: &mut Transform,
+    ) {
+        let local = match parent_pos {
+            Some(parent_pos) => pos.as_ivec2() - parent_pos.as_ivec2(),
+            None => self.offset.round(),
+        };
+        tran.translation.x = local.x as f32;
+        tran.translation.y = local.y as f32;
+    }
+}
+
+/// Moves everything with a `RelPos` to where its parent is, keeping the spatial hashes in sync
+pub(super) fn resolve_rel_pos<TriggerTxKind: TriggerKindTrait>(
+    rel_q: Query<(Entity, &RelPos, &ChildOf)>,
+    mut pos_q: Query<&mut Pos>,
+    mut stx_q: Query<(&StaticTx, &mut SpatKeys<SpatHashStaticTx>)>,
+    mut ttx_q: Query<(
+        &TriggerTxGeneric<TriggerTxKind>,
+        &mut SpatKeys<SpatHashTriggerTx>,
+    )>,
+    mut spat_hash_stx: ResMut<SpatHash<SpatHashStaticTx>>,
+    mut spat_hash_ttx: ResMut<SpatHash<SpatHashTriggerTx>>,
+) {
+    for (eid, rel_pos, child_of) in &rel_q {
+        // Walk up until we find something absolute
+        let mut offset = rel_pos.offset;
+        let mut parent = child_of.parent();
+        while let Ok((_, parent_rel_pos, parent_child_of)) = rel_q.get(parent) {
+            offset += parent_rel_pos.offset;
+            parent = parent_child_of.parent();
+        }
+        let Ok(parent_pos) = pos_q.get(parent).copied() else {
+            continue;
+        };
+        let Ok(mut pos) = pos_q.get_mut(eid) else {
+            continue;
+        };
+        let target = parent_pos.as_fvec2() + offset;
+        if pos.as_fvec2() == target {
+            continue;
+        }
+        pos.x = target.x;
+        pos.y = target.y;
+        let pos = *pos;
+        if let Ok((stx, mut spat_keys)) = stx_q.get_mut(eid) {
+            let hboxes = stx.comps.iter().map(|c| c.hbox.clone()).collect();
+            *spat_keys = spat_hash_stx.update(eid, &spat_keys, pos, hboxes);
+        }
+        if let Ok((ttx, mut spat_keys)) = ttx_q.get_mut(eid) {
+            let hboxes = ttx.comps.iter().map(|c| c.hbox.clone()).collect();
+            *spat_keys = spat_hash_ttx.update(eid, &spat_keys, pos, hboxes);
+        }
+    }
+}
+
 impl From<Vec2> for Pos {
     fn from(value: Vec2) -> Self {
         Self::new(value.x, value.y)
     }
 }
+impl Terpable for Pos {
+    fn terp(start: &Self, stop: &Self, mode: &TerpMode, frac: Fx) -> Self {
+        Self {
+            x: Fx::terp(&start.x, &stop.x, mode, frac),
+            y: Fx::terp(&start.y, &stop.y, mode, frac),
+            z: Fx::terp(&start.z, &stop.z, mode, frac),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{
+        dyno::Dyno,
+        hbox::HBox,
+        statics::StaticTxKind,
+        test_utils::{step, test_app},
+    };
+
+    #[test]
+    fn chains_follow_the_top_and_rehash_as_they_go() {
+        let mut app = test_app(default());
+        let top = app
+            .world_mut()
+            .spawn((Pos::new(0, 0), Dyno::new(fx!(640), Fx::ZERO)))
+            .id();
+        let middle = app
+            .world_mut()
+            .spawn((RelPos::new(10, 0), ChildOf(top)))
+            .id();
+        let bottom = app
+            .world_mut()
+            .spawn((
+                RelPos::new(0, 5),
+                ChildOf(middle),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(4, 4)),
+            ))
+            .id();
+        step(&mut app, 10);
+
+        let pos_of = |eid: Entity| app.world().get::<Pos>(eid).unwrap().as_fvec2();
+        assert_eq!(pos_of(top), FVec2::new(100, 0));
+        assert_eq!(pos_of(middle), FVec2::new(110, 0));
+        assert_eq!(pos_of(bottom), FVec2::new(110, 5));
+        let hashed_at = |x: i32, y: i32| {
+            let spat_hash = app.world().resource::<SpatHash<SpatHashStaticTx>>();
+            let keys = spat_hash.get_keys(Pos::new(x, y), vec![HBox::new(4, 4)]);
+            spat_hash.get_eids(keys).contains(&bottom)
+        };
+        assert!(hashed_at(110, 5));
+        assert!(!hashed_at(10, 5));
+    }
+
+    #[test]
+    fn orphans_stay_put() {
+        let mut app = test_app(default());
+        let parent = app.world_mut().spawn(Pos::new(50, 50)).id();
+        let child = app
+            .world_mut()
+            .spawn((RelPos::new(1, 1), ChildOf(parent)))
+            .id();
+        let no_pos = app.world_mut().spawn(Transform::default()).id();
+        let orphan = app
+            .world_mut()
+            .spawn((Pos::new(7, 7), RelPos::new(1, 1), ChildOf(no_pos)))
+            .id();
+        step(&mut app, 1);
+        assert_eq!(
+            app.world().get::<Pos>(child).unwrap().as_fvec2(),
+            FVec2::new(51, 51)
+        );
+
+        // Children go with their parent, and nothing trips over the gap
+        app.world_mut().despawn(parent);
+        step(&mut app, 1);
+        assert!(app.world().get_entity(child).is_err());
+        // Nothing absolute above it, so nothing to follow
+        assert_eq!(
+            app.world().get::<Pos>(orphan).unwrap().as_fvec2(),
+            FVec2::new(7, 7)
+        );
+    }
+
+    #[test]
+    fn transforms_round_the_same_as_pos() {
+        let mut app = test_app(default());
+        let parent = app.world_mut().spawn(Pos::new(fx!(0.75), 0)).id();
+        let child = app
+            .world_mut()
+            .spawn((RelPos::new(fx!(0.5), 0), ChildOf(parent)))
+            .id();
+        step(&mut app, 1);
+
+        // 1.25 rounds to 1, even though both halves round up on their own
+        let x_of = |eid: Entity| app.world().get::<Transform>(eid).unwrap().translation.x;
+        assert_eq!(app.world().get::<Pos>(child).unwrap().as_ivec2().x, 1);
+        assert_eq!(x_of(parent) + x_of(child), 1.0);
+    }
+}
@@ -0,0 +1,187 @@
+//! Normally `StaticRx`s go right through each other. Anything with a `StaticRx` AND `Pushable` is
+//! solid to every other rx, and gets shoved out of the way when something moves into it.
+//! Crates, stacks of boxes, enemies that block each other, etc.
+
+use bevy::prelude::*;
+use fixed::traits::ToFixed;
+
+use crate::{
+    fx,
+    glue::{fvec::FVec2, Fx},
+    physics::{
+        carry,
+        colls::StaticCollRec,
+        pos::Pos,
+        spat_hash::{SpatHash, SpatHashStaticTx},
+        statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind},
+        tiles::TileColliders,
+    },
+};
+
+#[derive(Component, Clone, Debug)]
+pub struct Pushable {
+    /// How hard this is to push. Rxs without `Pushable` push as if they had a mass of one.
+    mass: Fx,
+}
+impl Default for Pushable {
+    fn default() -> Self {
+        Self { mass: Fx::ONE }
+    }
+}
+impl Pushable {
+    pub fn new<M: ToFixed>(mass: M) -> Self {
+        Self { mass: fx!(mass) }
+    }
+    pub fn get_mass(&self) -> Fx {
+        self.mass
+    }
+}
+
+/// Pushes a moving rx out of any other rxs it ended up inside of, where at least one of the two is
+/// `Pushable`. The other one takes a share of the push based on mass (none if it's not pushable),
+/// but only moves as far as static solids let it. The mover takes whatever's left.
+/// Only rxs the index has near the mover are checked, and the ones that get pushed are kept up to
+/// date in it.
+/// NOTE: Pushed rxs only check statics, not other rxs. Chains of crates sort themselves out when
+///       the pushed crate gets its own turn to move.
+pub(super) fn resolve_pushes(
+    my_eid: Entity,
+    my_pos: &mut Pos,
+    my_vel: &mut FVec2,
+    my_srx: &StaticRx,
+    my_mass: Option<Fx>,
+    rx_index: &mut carry::RxIndex,
+    pushable_q: &Query<&Pushable>,
+    max_step: Fx,
+    pos_q: &mut Query<&mut Pos>,
+    srx_q: &Query<(Entity, &mut StaticRx)>,
+    stx_q: &Query<(Entity, &StaticTx)>,
+    spat_hash_stx: &SpatHash<SpatHashStaticTx>,
+    tiles: &TileColliders,
+    static_colls: &mut Vec<StaticCollRec>,
+) {
+    let others = rx_index.get_near(my_srx.get_thboxes(*my_pos).iter());
+    for other_eid in &others {
+        let other_mass = pushable_q.get(*other_eid).ok().map(|p| p.get_mass());
+        if *other_eid == my_eid || (my_mass.is_none() && other_mass.is_none()) {
+            continue;
+        }
+        let Ok((_, other_srx)) = srx_q.get(*other_eid) else {
+            continue;
+        };
+        for my_comp in my_srx
+            .comps
+            .iter()
+            .filter(|comp| comp.kind != StaticRxKind::Observe)
+        {
+            for other_comp in other_srx
+                .comps
+                .iter()
+                .filter(|comp| comp.kind != StaticRxKind::Observe)
+                .filter(|comp| my_comp.layers.interacts_with(&comp.layers))
+            {
+                // They may have been moved by an earlier pair, so look them up every time
+                let Ok(other_pos) = pos_q.get(*other_eid).copied() else {
+                    continue;
+                };
+                let my_thbox = my_comp.hbox.translated(my_pos.as_fvec2());
+                let other_thbox = other_comp.hbox.translated(other_pos.as_fvec2());
+                let Some(push) = my_thbox.get_push_out(&other_thbox) else {
+                    continue;
+                };
+
+                // Split the push by mass, then see how much of their share they can actually take
+                let their_share = match other_mass {
+                    Some(other_mass) => {
+                        let my_mass = my_mass.unwrap_or(Fx::ONE);
+                        my_mass / (my_mass + other_mass)
+                    }
+                    None => Fx::ZERO,
+                };
+                let wanted = -(push * their_share);
+                let moved = if wanted == FVec2::ZERO {
+                    FVec2::ZERO
+                } else {
+                    carry::move_rx_blocked(
+                        *other_eid,
+                        other_srx,
+                        wanted,
+                        my_eid,
+                        max_step,
+                        pos_q,
+                        stx_q,
+                        spat_hash_stx,
+                        tiles,
+                    )
+                };
+                if let (true, Ok(moved_pos)) = (moved != FVec2::ZERO, pos_q.get(*other_eid)) {
+                    rx_index.update(*other_eid, *moved_pos, other_srx);
+                }
+                let my_push = push + moved;
+
+                let normal = push.try_normalize().unwrap_or_default();
+                let old_perp = normal * my_vel.dot(normal);
+                let old_par = *my_vel - old_perp;
+                static_colls.push(StaticCollRec {
+                    push: my_push,
+                    rx_pos: *my_pos,
+                    rx_perp: old_perp,
+                    rx_par: old_par,
+                    rx_ctrl: my_eid,
+                    rx_kind: my_comp.kind,
+                    rx_hbox: my_comp.hbox.get_marker(),
+                    tx_pos: other_pos,
+                    tx_ctrl: *other_eid,
+                    tx_kind: StaticTxKind::Solid,
+                    tx_hbox: other_comp.hbox.get_marker(),
+                    tx_normal: normal,
+                    pass_ignored: false,
+                });
+                *my_pos += my_push;
+                // If they couldn't get out of the way, they're as good as a wall
+                if moved != wanted && old_perp.dot(push) < Fx::ZERO {
+                    *my_vel = old_par;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{
+        dyno::Dyno,
+        hbox::HBox,
+        test_utils::{step, test_app},
+    };
+
+    #[test]
+    fn movers_shove_pushables_and_leave_everything_else_alone() {
+        let mut app = test_app(default());
+        let mut spawn_body = |x: i32, y: i32, vel: i32| {
+            app.world_mut()
+                .spawn((
+                    Pos::new(x, y),
+                    Dyno::new(fx!(vel), Fx::ZERO),
+                    StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+                ))
+                .id()
+        };
+        let mover = spawn_body(0, 0, 64);
+        let far_mover = spawn_body(0, 100, 64);
+        let not_pushable = spawn_body(6, 100, 0);
+        let crate_ = spawn_body(6, 0, 0);
+        app.world_mut()
+            .entity_mut(crate_)
+            .insert(Pushable::default());
+        step(&mut app, 10);
+
+        let x_of = |eid: Entity| app.world().get::<Pos>(eid).unwrap().x;
+        assert!(x_of(crate_) > fx!(6));
+        assert!(x_of(crate_) - x_of(mover) >= fx!(4));
+        // Plain rxs still go right through each other
+        assert_eq!(x_of(far_mover), fx!(10));
+        assert_eq!(x_of(not_pushable), fx!(6));
+    }
+}
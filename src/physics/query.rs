@@ -0,0 +1,588 @@
+//! Read-only geometry queries against the world.
+//! Lets you ask "what would I hit?" BEFORE anything moves, instead of moving a `StaticRx`
+//! and reading `StaticColls` afterwards. Handy for ledge detection, line-of-sight, lasers,
+//! grounded probes, etc.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    fx,
+    glue::{fvec::FVec2, Fx},
+    physics::{
+        hbox::{HBox, HBoxMarker},
+        layers::PhysicsLayers,
+        pos::Pos,
+        spat_hash::{SpatHash, SpatHashKind, SpatHashStaticTx, SpatHashTriggerTx},
+        statics::{StaticTx, StaticTxKind},
+        tiles::TileColliders,
+        triggers::{TriggerKindTrait, TriggerTxGeneric},
+    },
+};
+
+/// The result of casting a ray or an hbox into the `StaticTx`s in the world
+#[derive(Clone, Debug)]
+pub struct StaticCastHit {
+    /// Entity of the control associated with the tx that was hit
+    pub tx_ctrl: Entity,
+    /// The kind of the tx that was hit
+    pub tx_kind: StaticTxKind,
+    /// The marker of the hbox on the tx that was hit
+    pub tx_hbox: HBoxMarker,
+    /// Where the origin of the cast is at the moment of first contact.
+    /// For rays this is the point of contact itself.
+    pub point: FVec2,
+    /// Normal of the surface that was hit. Zero if the cast started inside the tx.
+    pub normal: FVec2,
+    /// How far along the cast the first contact happened
+    pub dist: Fx,
+}
+
+/// The result of casting a ray or an hbox into the `TriggerTxGeneric`s in the world
+#[derive(Clone, Debug)]
+pub struct TriggerCastHit<TriggerTxKind: TriggerKindTrait> {
+    /// Entity of the control associated with the tx that was hit
+    pub tx_ctrl: Entity,
+    /// The kind of the tx that was hit
+    pub tx_kind: TriggerTxKind,
+    /// The marker of the hbox on the tx that was hit
+    pub tx_hbox: HBoxMarker,
+    /// Where the origin of the cast is at the moment of first contact.
+    /// For rays this is the point of contact itself.
+    pub point: FVec2,
+    /// Normal of the surface that was hit. Zero if the cast started inside the tx.
+    pub normal: FVec2,
+    /// How far along the cast the first contact happened
+    pub dist: Fx,
+}
+
+/// Slab test of a ray against an axis-aligned box given by its min and max corners,
+/// optionally cut by a slope plane (everything inside has `normal.dot(p) <= d`).
+/// Returns the distance along the ray to the first contact, and the normal of the face that was hit.
+fn ray_vs_bounds(
+    origin: FVec2,
+    dir: FVec2,
+    max_dist: Fx,
+    min: FVec2,
+    max: FVec2,
+    slope: Option<(FVec2, Fx)>,
+) -> Option<(Fx, FVec2)> {
+    let mut t_near = Fx::MIN;
+    let mut t_far = Fx::MAX;
+    let mut normal = FVec2::ZERO;
+    for (o, d, lo, hi, axis) in [
+        (origin.x, dir.x, min.x, max.x, FVec2::X),
+        (origin.y, dir.y, min.y, max.y, FVec2::Y),
+    ] {
+        // NOTE: checked_div fails on zero AND on overflow. Both mean "basically parallel to this axis".
+        match (lo - o).checked_div(d).zip((hi - o).checked_div(d)) {
+            Some((t1, t2)) => {
+                let (t_enter, t_exit) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+                if t_enter > t_near {
+                    t_near = t_enter;
+                    normal = axis * -d.signum();
+                }
+                t_far = t_far.min(t_exit);
+            }
+            None => {
+                if o <= lo || hi <= o {
+                    return None;
+                }
+            }
+        }
+    }
+    if let Some((slope_normal, slope_d)) = slope {
+        let towards = slope_normal.dot(dir);
+        let room = slope_d - slope_normal.dot(origin);
+        match room.checked_div(towards) {
+            Some(t) => {
+                if towards < Fx::ZERO {
+                    if t > t_near {
+                        t_near = t;
+                        normal = slope_normal.try_normalize().unwrap_or_default();
+                    }
+                } else {
+                    t_far = t_far.min(t);
+                }
+            }
+            None => {
+                if room <= Fx::ZERO {
+                    return None;
+                }
+            }
+        }
+    }
+    if t_near >= t_far || t_far <= Fx::ZERO || t_near > max_dist {
+        return None;
+    }
+    if t_near < Fx::ZERO {
+        // Started inside
+        return Some((Fx::ZERO, FVec2::ZERO));
+    }
+    Some((t_near, normal))
+}
+
+/// Everything needed to describe a single cast.
+/// A ray is just a cast with no hbox.
+struct CastSpec<'a> {
+    origin: FVec2,
+    dir: FVec2,
+    max_dist: Fx,
+    hbox: Option<&'a HBox>,
+    layers: PhysicsLayers,
+}
+impl CastSpec<'_> {
+    /// The world-space region the cast sweeps through
+    fn swept_bounds(&self) -> (FVec2, FVec2) {
+        let stop = self.origin + self.dir * self.max_dist;
+        let (mut min, mut max) = (
+            FVec2::new(self.origin.x.min(stop.x), self.origin.y.min(stop.y)),
+            FVec2::new(self.origin.x.max(stop.x), self.origin.y.max(stop.y)),
+        );
+        if let Some(hbox) = self.hbox {
+            min += FVec2::new(hbox.min_x(), hbox.min_y());
+            max += FVec2::new(hbox.max_x(), hbox.max_y());
+        }
+        (min, max)
+    }
+
+    /// Casts against a single translated hbox
+    /// NOTE: Circles and capsules (ours or theirs) are cast as their whole box, so hits can come a bit early.
+    fn against(&self, thbox: &HBox) -> Option<(Fx, FVec2)> {
+        match self.hbox {
+            None => ray_vs_bounds(
+                self.origin,
+                self.dir,
+                self.max_dist,
+                thbox.bottom_left(),
+                thbox.top_right(),
+                thbox.slope_plane(),
+            ),
+            Some(hbox) => {
+                // Sweeping a box is the same as casting a ray from its center against the
+                // other box grown by our half size. Slopes get pushed out by however far our
+                // box reaches in the direction of the slope.
+                let half_size = (hbox.top_right() - hbox.bottom_left()) / fx!(2);
+                let slope = thbox.slope_plane().map(|(normal, d)| {
+                    (
+                        normal,
+                        d + normal.x.abs() * half_size.x + normal.y.abs() * half_size.y,
+                    )
+                });
+                ray_vs_bounds(
+                    self.origin + hbox.get_offset(),
+                    self.dir,
+                    self.max_dist,
+                    thbox.bottom_left() - half_size,
+                    thbox.top_right() + half_size,
+                    slope,
+                )
+            }
+        }
+    }
+}
+
+/// How far the `movers` (already translated) can go along `dir` before first touching one of the
+/// `others` they interact with. Things they start inside of don't count.
+pub(super) fn sweep_dist(
+    movers: &[(PhysicsLayers, HBox)],
+    dir: FVec2,
+    max_dist: Fx,
+    others: &[(PhysicsLayers, HBox)],
+) -> Option<Fx> {
+    let mut result: Option<Fx> = None;
+    for (layers, hbox) in movers {
+        let spec = CastSpec {
+            origin: FVec2::ZERO,
+            dir,
+            max_dist,
+            hbox: Some(hbox),
+            layers: *layers,
+        };
+        for (_, other) in others
+            .iter()
+            .filter(|(other_layers, _)| spec.layers.interacts_with(other_layers))
+        {
+            match spec.against(other) {
+                Some((dist, normal)) if normal != FVec2::ZERO => {
+                    result = Some(result.map_or(dist, |other_dist| other_dist.min(dist)));
+                }
+                _ => {}
+            }
+        }
+    }
+    result
+}
+
+/// Deterministic ordering of hits: closest first, ties broken by entity then marker.
+fn hit_order(a: (Fx, Entity, HBoxMarker), b: (Fx, Entity, HBoxMarker)) -> std::cmp::Ordering {
+    a.0.cmp(&b.0)
+        .then_with(|| a.1.cmp(&b.1))
+        .then_with(|| a.2.cmp(&b.2))
+}
+
+/// SystemParam for querying statics and triggers without moving anything.
+/// Every cast takes the `PhysicsLayers` to filter with, same as a rx would be filtered
+/// (`PhysicsLayers::ALL` to hit everything).
+/// NOTE: This reads `Pos`, so systems using it can't also mutably query the `Pos` of statics
+/// or triggers without a `ParamSet` or a disjoint filter.
+#[derive(SystemParam)]
+pub struct PhysicsQueryGeneric<'w, 's, TriggerTxKind: TriggerKindTrait> {
+    pos_q: Query<'w, 's, &'static Pos>,
+    stx_q: Query<'w, 's, &'static StaticTx>,
+    ttx_q: Query<'w, 's, &'static TriggerTxGeneric<TriggerTxKind>>,
+    spat_hash_stx: Res<'w, SpatHash<SpatHashStaticTx>>,
+    spat_hash_ttx: Res<'w, SpatHash<SpatHashTriggerTx>>,
+    tiles: Res<'w, TileColliders>,
+}
+impl<TriggerTxKind: TriggerKindTrait> PhysicsQueryGeneric<'_, '_, TriggerTxKind> {
+    fn candidate_eids<K: SpatHashKind>(spat_hash: &SpatHash<K>, spec: &CastSpec) -> Vec<Entity> {
+        let (min, max) = spec.swept_bounds();
+        let mut eids = spat_hash
+            .get_eids(spat_hash.get_region_keys(min, max))
+            .into_iter()
+            .collect::<Vec<_>>();
+        // Hash sets don't iterate deterministically, but we want to
+        eids.sort();
+        eids
+    }
+
+    fn cast_static(&self, spec: CastSpec) -> Vec<StaticCastHit> {
+        let mut hits = vec![];
+        for eid in Self::candidate_eids(&self.spat_hash_stx, &spec) {
+            let (Ok(stx), Ok(pos)) = (self.stx_q.get(eid), self.pos_q.get(eid)) else {
+                continue;
+            };
+            for comp in stx
+                .comps
+                .iter()
+                .filter(|comp| spec.layers.interacts_with(&comp.layers))
+            {
+                let thbox = comp.hbox.translated(pos.as_fvec2());
+                let Some((dist, normal)) = spec.against(&thbox) else {
+                    continue;
+                };
+                hits.push(StaticCastHit {
+                    tx_ctrl: eid,
+                    tx_kind: comp.kind,
+                    tx_hbox: comp.hbox.get_marker(),
+                    point: spec.origin + spec.dir * dist,
+                    normal,
+                    dist,
+                });
+            }
+        }
+        let (min, max) = spec.swept_bounds();
+        for tile in self
+            .tiles
+            .get_region(min, max)
+            .into_iter()
+            .filter(|tile| spec.layers.interacts_with(&tile.layers))
+        {
+            let Some((dist, normal)) = spec.against(&tile.thbox) else {
+                continue;
+            };
+            hits.push(StaticCastHit {
+                tx_ctrl: tile.ctrl,
+                tx_kind: tile.kind,
+                tx_hbox: tile.thbox.get_marker(),
+                point: spec.origin + spec.dir * dist,
+                normal,
+                dist,
+            });
+        }
+        hits.sort_by(|a, b| {
+            hit_order(
+                (a.dist, a.tx_ctrl, a.tx_hbox),
+                (b.dist, b.tx_ctrl, b.tx_hbox),
+            )
+        });
+        hits
+    }
+
+    fn cast_trigger(&self, spec: CastSpec) -> Vec<TriggerCastHit<TriggerTxKind>> {
+        let mut hits = vec![];
+        for eid in Self::candidate_eids(&self.spat_hash_ttx, &spec) {
+            let (Ok(ttx), Ok(pos)) = (self.ttx_q.get(eid), self.pos_q.get(eid)) else {
+                continue;
+            };
+            for comp in ttx
+                .comps
+                .iter()
+                .filter(|comp| spec.layers.interacts_with(&comp.layers))
+            {
+                let thbox = comp.hbox.translated(pos.as_fvec2());
+                let Some((dist, normal)) = spec.against(&thbox) else {
+                    continue;
+                };
+                hits.push(TriggerCastHit {
+                    tx_ctrl: eid,
+                    tx_kind: comp.kind.clone(),
+                    tx_hbox: comp.hbox.get_marker(),
+                    point: spec.origin + spec.dir * dist,
+                    normal,
+                    dist,
+                });
+            }
+        }
+        hits.sort_by(|a, b| {
+            hit_order(
+                (a.dist, a.tx_ctrl, a.tx_hbox),
+                (b.dist, b.tx_ctrl, b.tx_hbox),
+            )
+        });
+        hits
+    }
+
+    /// Casts a ray into the statics, returning the first hit (if any)
+    pub fn raycast_static(
+        &self,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Option<StaticCastHit> {
+        self.raycast_static_all(origin, dir, max_dist, layers)
+            .into_iter()
+            .next()
+    }
+    /// Casts a ray into the statics, returning every hit ordered by distance
+    pub fn raycast_static_all(
+        &self,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Vec<StaticCastHit> {
+        let Some(dir) = dir.try_normalize() else {
+            return vec![];
+        };
+        self.cast_static(CastSpec {
+            origin,
+            dir,
+            max_dist,
+            hbox: None,
+            layers,
+        })
+    }
+    /// Sweeps an hbox (positioned relative to `origin`) into the statics, returning the first hit (if any)
+    pub fn shapecast_static(
+        &self,
+        hbox: &HBox,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Option<StaticCastHit> {
+        self.shapecast_static_all(hbox, origin, dir, max_dist, layers)
+            .into_iter()
+            .next()
+    }
+    /// Sweeps an hbox (positioned relative to `origin`) into the statics, returning every hit ordered by distance
+    pub fn shapecast_static_all(
+        &self,
+        hbox: &HBox,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Vec<StaticCastHit> {
+        let Some(dir) = dir.try_normalize() else {
+            return vec![];
+        };
+        self.cast_static(CastSpec {
+            origin,
+            dir,
+            max_dist,
+            hbox: Some(hbox),
+            layers,
+        })
+    }
+
+    /// Casts a ray into the triggers, returning the first hit (if any)
+    pub fn raycast_trigger(
+        &self,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Option<TriggerCastHit<TriggerTxKind>> {
+        self.raycast_trigger_all(origin, dir, max_dist, layers)
+            .into_iter()
+            .next()
+    }
+    /// Casts a ray into the triggers, returning every hit ordered by distance
+    pub fn raycast_trigger_all(
+        &self,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Vec<TriggerCastHit<TriggerTxKind>> {
+        let Some(dir) = dir.try_normalize() else {
+            return vec![];
+        };
+        self.cast_trigger(CastSpec {
+            origin,
+            dir,
+            max_dist,
+            hbox: None,
+            layers,
+        })
+    }
+    /// Sweeps an hbox (positioned relative to `origin`) into the triggers, returning the first hit (if any)
+    pub fn shapecast_trigger(
+        &self,
+        hbox: &HBox,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Option<TriggerCastHit<TriggerTxKind>> {
+        self.shapecast_trigger_all(hbox, origin, dir, max_dist, layers)
+            .into_iter()
+            .next()
+    }
+    /// Sweeps an hbox (positioned relative to `origin`) into the triggers, returning every hit ordered by distance
+    pub fn shapecast_trigger_all(
+        &self,
+        hbox: &HBox,
+        origin: FVec2,
+        dir: FVec2,
+        max_dist: Fx,
+        layers: PhysicsLayers,
+    ) -> Vec<TriggerCastHit<TriggerTxKind>> {
+        let Some(dir) = dir.try_normalize() else {
+            return vec![];
+        };
+        self.cast_trigger(CastSpec {
+            origin,
+            dir,
+            max_dist,
+            hbox: Some(hbox),
+            layers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate::physics::{
+        statics::StaticTxHBoxes,
+        test_utils::{step, test_app, TestTriggerRx, TestTriggerTx},
+        tiles::TileGrid,
+        triggers::TriggerRxGeneric,
+    };
+
+    fn spawn_block(app: &mut App, x: i32, y: i32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(x, y),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(4, 4)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn raycast_hits_come_back_closest_first_then_by_entity() {
+        let mut app = test_app(default());
+        let far = spawn_block(&mut app, 30, 0);
+        let near = spawn_block(&mut app, 10, 0);
+        let tied_a = spawn_block(&mut app, 20, 0);
+        let tied_b = spawn_block(&mut app, 20, 0);
+        step(&mut app, 1);
+
+        let mut state = SystemState::<PhysicsQueryGeneric<TestTriggerTx>>::new(app.world_mut());
+        let query = state.get(app.world());
+        let hits =
+            query.raycast_static_all(FVec2::new(-10, 0), FVec2::X, fx!(100), PhysicsLayers::ALL);
+        assert_eq!(
+            hits.iter().map(|hit| hit.tx_ctrl).collect::<Vec<_>>(),
+            vec![near, tied_a.min(tied_b), tied_a.max(tied_b), far]
+        );
+        assert_eq!(hits[0].dist, fx!(18));
+        assert_eq!(hits[0].point, FVec2::new(8, 0));
+        assert_eq!(hits[0].normal, FVec2::new(-1, 0));
+        let first =
+            query.raycast_static(FVec2::new(-10, 0), FVec2::X, fx!(100), PhysicsLayers::ALL);
+        assert_eq!(first.map(|hit| hit.tx_ctrl), Some(near));
+    }
+
+    #[test]
+    fn resized_txs_show_up_in_queries_right_away() {
+        let mut app = test_app(default());
+        let block = spawn_block(&mut app, 50, 0);
+        step(&mut app, 1);
+
+        let mut hboxes_state = SystemState::<StaticTxHBoxes>::new(app.world_mut());
+        hboxes_state
+            .get_mut(app.world_mut())
+            .set(block, [(StaticTxKind::Solid, HBox::new(120, 4))]);
+        // No physics step in between
+        let mut state = SystemState::<PhysicsQueryGeneric<TestTriggerTx>>::new(app.world_mut());
+        let query = state.get(app.world());
+        let hit = query.raycast_static(FVec2::new(0, 20), -FVec2::Y, fx!(40), PhysicsLayers::ALL);
+        assert_eq!(
+            hit.map(|hit| (hit.tx_ctrl, hit.dist)),
+            Some((block, fx!(18)))
+        );
+    }
+
+    #[test]
+    fn shapecast_mixes_tiles_and_txs_in_distance_order() {
+        let mut app = test_app(default());
+        let block = spawn_block(&mut app, 4, 20);
+        let grid_ctrl = app.world_mut().spawn_empty().id();
+        let mut grid = TileGrid::new(8);
+        grid.set(IVec2::new(0, 1), Some(StaticTxKind::Solid));
+        app.world_mut()
+            .resource_mut::<TileColliders>()
+            .insert_grid(grid_ctrl, grid);
+        step(&mut app, 1);
+
+        let mut state = SystemState::<PhysicsQueryGeneric<TestTriggerTx>>::new(app.world_mut());
+        let query = state.get(app.world());
+        // The tile covers y 8..16, the block 18..22. A 2x2 box starting at y 0 touches the tile first.
+        let hits = query.shapecast_static_all(
+            &HBox::new(2, 2),
+            FVec2::new(4, 0),
+            FVec2::Y,
+            fx!(100),
+            PhysicsLayers::ALL,
+        );
+        assert_eq!(
+            hits.iter().map(|hit| hit.tx_ctrl).collect::<Vec<_>>(),
+            vec![grid_ctrl, block]
+        );
+        assert_eq!(hits[0].dist, fx!(7));
+        assert_eq!(hits[1].dist, fx!(17));
+        assert!(hits.iter().all(|hit| hit.normal == FVec2::new(0, -1)));
+    }
+
+    #[test]
+    fn casts_respect_layers_and_only_see_txs() {
+        let mut app = test_app(default());
+        let zone = app
+            .world_mut()
+            .spawn((
+                Pos::new(10, 0),
+                TriggerTxGeneric::single(TestTriggerTx::Zone, HBox::new(4, 4))
+                    .with_layers(PhysicsLayers::new(2, u32::MAX)),
+            ))
+            .id();
+        app.world_mut().spawn((
+            Pos::new(5, 0),
+            TriggerRxGeneric::single(TestTriggerRx::Body, HBox::new(4, 4)),
+        ));
+        step(&mut app, 1);
+
+        let mut state = SystemState::<PhysicsQueryGeneric<TestTriggerTx>>::new(app.world_mut());
+        let query = state.get(app.world());
+        let hit = query.raycast_trigger(FVec2::ZERO, FVec2::X, fx!(100), PhysicsLayers::ALL);
+        assert_eq!(hit.map(|hit| hit.tx_ctrl), Some(zone));
+        let hit = query.raycast_trigger(FVec2::ZERO, FVec2::X, fx!(100), PhysicsLayers::new(1, 1));
+        assert!(hit.is_none());
+    }
+}
@@ -0,0 +1,423 @@
+//! Saving and restoring the exact state of the physics world.
+//! Everything is `Fx`, so a restore puts things back bit-for-bit. Combined with `Deterministic`
+//! (or a fixed timestep), stepping forward again from a restore replays exactly the same way.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    glue::bullet_time::BulletTime,
+    physics::{
+        colls::{StaticColls, TriggerCollsGeneric},
+        contacts::Contacts,
+        controller::PlatformerController,
+        dyno::{Dyno, DynoBody},
+        medium::MediumOverlaps,
+        pos::{Pos, RelPos},
+        spat_hash::{SpatHash, SpatHashStaticTx, SpatHashTriggerTx},
+        statics::{StaticRx, StaticTx},
+        tiles::TileColliders,
+        timestep::PrevPos,
+        trigger_events::TriggerPairsGeneric,
+        triggers::{TriggerKindTrait, TriggerRxGeneric, TriggerTxGeneric},
+    },
+};
+
+#[derive(Clone)]
+struct EntitySnapshot<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> {
+    pos: Pos,
+    rel_pos: Option<RelPos>,
+    dyno: Option<Dyno>,
+    dyno_body: Option<DynoBody>,
+    controller: Option<PlatformerController>,
+    contacts: Option<Contacts>,
+    srx: Option<StaticRx>,
+    stx: Option<StaticTx>,
+    trx: Option<TriggerRxGeneric<TriggerRxKind>>,
+    ttx: Option<TriggerTxGeneric<TriggerTxKind>>,
+}
+
+/// The physics state of the whole world at one moment.
+/// NOTE: Only entities that still exist get restored. Things spawned after the snapshot are left alone.
+#[derive(Clone)]
+pub struct PhysicsSnapshotGeneric<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
+{
+    ents: Vec<(Entity, EntitySnapshot<TriggerRxKind, TriggerTxKind>)>,
+    static_colls: StaticColls,
+    trigger_colls: TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>,
+    trigger_pairs: TriggerPairsGeneric<TriggerRxKind, TriggerTxKind>,
+    medium_overlaps: MediumOverlaps,
+    tiles: TileColliders,
+    bullet_time: BulletTime,
+}
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
+    PhysicsSnapshotGeneric<TriggerRxKind, TriggerTxKind>
+{
+    pub fn take(world: &mut World) -> Self {
+        let mut q = world.query::<(
+            Entity,
+            &Pos,
+            Option<&RelPos>,
+            Option<&Dyno>,
+            Option<&DynoBody>,
+            Option<&PlatformerController>,
+            Option<&Contacts>,
+            Option<&StaticRx>,
+            Option<&StaticTx>,
+            Option<&TriggerRxGeneric<TriggerRxKind>>,
+            Option<&TriggerTxGeneric<TriggerTxKind>>,
+        )>();
+        let mut ents = q
+            .iter(world)
+            .map(
+                |(eid, pos, rel_pos, dyno, dyno_body, controller, contacts, srx, stx, trx, ttx)| {
+                    (
+                        eid,
+                        EntitySnapshot {
+                            pos: *pos,
+                            rel_pos: rel_pos.copied(),
+                            dyno: dyno.cloned(),
+                            dyno_body: dyno_body.cloned(),
+                            controller: controller.cloned(),
+                            contacts: contacts.cloned(),
+                            srx: srx.cloned(),
+                            stx: stx.cloned(),
+                            trx: trx.cloned(),
+                            ttx: ttx.cloned(),
+                        },
+                    )
+                },
+            )
+            .collect::<Vec<_>>();
+        ents.sort_by_key(|(eid, _)| *eid);
+        Self {
+            ents,
+            static_colls: world.resource::<StaticColls>().clone(),
+            trigger_colls: world
+                .resource::<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>()
+                .clone(),
+            trigger_pairs: world
+                .resource::<TriggerPairsGeneric<TriggerRxKind, TriggerTxKind>>()
+                .clone(),
+            medium_overlaps: world.resource::<MediumOverlaps>().clone(),
+            tiles: world.resource::<TileColliders>().clone(),
+            bullet_time: world.resource::<BulletTime>().clone(),
+        }
+    }
+
+    pub fn restore(&self, world: &mut World) {
+        macro_rules! restore_comp {
+            ($ent:expr, $ty:ty, $val:expr) => {{
+                match $val {
+                    Some(val) => {
+                        if $ent.contains::<$ty>() {
+                            *$ent.get_mut::<$ty>().unwrap() = val.clone();
+                        } else {
+                            $ent.insert(val.clone());
+                        }
+                    }
+                    None => {
+                        $ent.remove::<$ty>();
+                    }
+                }
+            }};
+        }
+        for (eid, snap) in &self.ents {
+            let Ok(mut ent) = world.get_entity_mut(*eid) else {
+                continue;
+            };
+            // Pos first so any txs that get re-added hash in the right spot
+            *ent.get_mut::<Pos>().expect("Pos in snapshot") = snap.pos;
+            if let Some(mut prev) = ent.get_mut::<PrevPos>() {
+                prev.0 = snap.pos;
+            }
+            restore_comp!(ent, RelPos, &snap.rel_pos);
+            restore_comp!(ent, Dyno, &snap.dyno);
+            restore_comp!(ent, DynoBody, &snap.dyno_body);
+            restore_comp!(ent, PlatformerController, &snap.controller);
+            restore_comp!(ent, Contacts, &snap.contacts);
+            restore_comp!(ent, StaticRx, &snap.srx);
+            restore_comp!(ent, StaticTx, &snap.stx);
+            restore_comp!(ent, TriggerRxGeneric<TriggerRxKind>, &snap.trx);
+            restore_comp!(ent, TriggerTxGeneric<TriggerTxKind>, &snap.ttx);
+        }
+        world.flush();
+
+        // Easier to rebuild the hashes from scratch than to work out what moved
+        macro_rules! rebuild_spat_hash {
+            ($kind:ty, $ctrl:ty) => {{
+                world.resource_mut::<SpatHash<$kind>>().clear();
+                let mut q = world.query::<(Entity, &Pos, &$ctrl)>();
+                let data = q
+                    .iter(world)
+                    .map(|(eid, pos, ctrl)| {
+                        let hboxes = ctrl.comps.iter().map(|c| c.hbox.clone()).collect();
+                        (eid, *pos, hboxes)
+                    })
+                    .collect::<Vec<_>>();
+                for (eid, pos, hboxes) in data {
+                    let keys = world
+                        .resource_mut::<SpatHash<$kind>>()
+                        .insert(eid, pos, hboxes);
+                    world.entity_mut(eid).insert(keys);
+                }
+            }};
+        }
+        rebuild_spat_hash!(SpatHashStaticTx, StaticTx);
+        rebuild_spat_hash!(SpatHashTriggerTx, TriggerTxGeneric<TriggerTxKind>);
+
+        world.insert_resource(self.static_colls.clone());
+        world.insert_resource(self.trigger_colls.clone());
+        world.insert_resource(self.trigger_pairs.clone());
+        world.insert_resource(self.medium_overlaps.clone());
+        world.insert_resource(self.tiles.clone());
+        world.insert_resource(self.bullet_time.clone());
+    }
+}
+
+/// A rolling history of snapshots, one per physics step. Empty (and free) until given a capacity.
+#[derive(Resource)]
+pub struct PhysicsHistoryGeneric<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> {
+    capacity: usize,
+    snapshots: VecDeque<PhysicsSnapshotGeneric<TriggerRxKind, TriggerTxKind>>,
+}
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> Default
+    for PhysicsHistoryGeneric<TriggerRxKind, TriggerTxKind>
+{
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            snapshots: default(),
+        }
+    }
+}
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
+    PhysicsHistoryGeneric<TriggerRxKind, TriggerTxKind>
+{
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+    /// How many steps to remember. Zero turns recording off.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.snapshots.len() > capacity {
+            self.snapshots.pop_front();
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+    /// The snapshot from `steps_ago` physics steps ago. Zero is the most recent step.
+    pub fn get(
+        &self,
+        steps_ago: usize,
+    ) -> Option<&PhysicsSnapshotGeneric<TriggerRxKind, TriggerTxKind>> {
+        let ix = self.snapshots.len().checked_sub(steps_ago + 1)?;
+        self.snapshots.get(ix)
+    }
+}
+
+/// Restores a snapshot (at the end of the current command flush)
+#[derive(Event)]
+pub struct RestorePhysicsGeneric<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> {
+    snapshot: PhysicsSnapshotGeneric<TriggerRxKind, TriggerTxKind>,
+}
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
+    RestorePhysicsGeneric<TriggerRxKind, TriggerTxKind>
+{
+    pub fn new(snapshot: PhysicsSnapshotGeneric<TriggerRxKind, TriggerTxKind>) -> Self {
+        Self { snapshot }
+    }
+}
+fn handle_restore_physics<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
+    trigger: On<RestorePhysicsGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut commands: Commands,
+) {
+    let snapshot = trigger.event().snapshot.clone();
+    commands.queue(move |world: &mut World| snapshot.restore(world));
+}
+
+/// Goes back `steps` physics steps using the history, forgetting everything after
+#[derive(Event)]
+pub struct RewindPhysics {
+    steps: usize,
+}
+impl RewindPhysics {
+    pub fn new(steps: usize) -> Self {
+        Self { steps }
+    }
+}
+fn handle_rewind_physics<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>(
+    trigger: On<RewindPhysics>,
+    mut commands: Commands,
+    mut history: ResMut<PhysicsHistoryGeneric<TriggerRxKind, TriggerTxKind>>,
+) {
+    if history.is_empty() {
+        warn!("Can't RewindPhysics with an empty history");
+        return;
+    }
+    let steps = trigger.event().steps.min(history.len() - 1);
+    for _ in 0..steps {
+        history.snapshots.pop_back();
+    }
+    let snapshot = history.snapshots.back().unwrap().clone();
+    commands.queue(move |world: &mut World| snapshot.restore(world));
+}
+
+pub(super) fn record_physics_history<
+    TriggerRxKind: TriggerKindTrait,
+    TriggerTxKind: TriggerKindTrait,
+>(
+    world: &mut World,
+) {
+    let capacity = world
+        .resource::<PhysicsHistoryGeneric<TriggerRxKind, TriggerTxKind>>()
+        .capacity;
+    if capacity == 0 {
+        return;
+    }
+    let snapshot = PhysicsSnapshotGeneric::<TriggerRxKind, TriggerTxKind>::take(world);
+    let mut history = world.resource_mut::<PhysicsHistoryGeneric<TriggerRxKind, TriggerTxKind>>();
+    history.snapshots.push_back(snapshot);
+    while history.snapshots.len() > capacity {
+        history.snapshots.pop_front();
+    }
+}
+
+pub(super) fn register_snapshot<
+    TriggerRxKind: TriggerKindTrait,
+    TriggerTxKind: TriggerKindTrait,
+>(
+    app: &mut App,
+) {
+    app.insert_resource(PhysicsHistoryGeneric::<TriggerRxKind, TriggerTxKind>::default());
+    app.add_observer(handle_restore_physics::<TriggerRxKind, TriggerTxKind>);
+    app.add_observer(handle_rewind_physics::<TriggerRxKind, TriggerTxKind>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fx,
+        glue::{fvec::FVec2, Fx},
+        physics::{
+            hbox::HBox,
+            statics::{StaticRxKind, StaticTxKind},
+            test_utils::{step, test_app, TestSettings, TestTriggerRx, TestTriggerTx},
+            tiles::TileGrid,
+        },
+    };
+
+    type TestSnapshot = PhysicsSnapshotGeneric<TestTriggerRx, TestTriggerTx>;
+
+    fn spawn_world(app: &mut App) -> Entity {
+        let grid_ctrl = app.world_mut().spawn_empty().id();
+        let mut grid = TileGrid::new(8);
+        for x in -8..8 {
+            grid.set(IVec2::new(x, -1), Some(StaticTxKind::Solid));
+        }
+        app.world_mut()
+            .resource_mut::<TileColliders>()
+            .insert_grid(grid_ctrl, grid);
+        app.world_mut().spawn((
+            Pos::new(20, 20),
+            Dyno::new(fx!(32), Fx::ZERO),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(16, 4)),
+        ));
+        let body = app
+            .world_mut()
+            .spawn((
+                Pos::new(0, 30),
+                Dyno::new(fx!(20), Fx::ZERO),
+                DynoBody::default().with_friction(40, 5),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id();
+        app.world_mut().spawn((
+            ChildOf(body),
+            RelPos::new(0, 6),
+            TriggerTxGeneric::single(TestTriggerTx::Zone, HBox::new(2, 2)),
+        ));
+        app.world_mut().spawn((
+            Pos::new(22, 40),
+            DynoBody::default(),
+            StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            TriggerRxGeneric::single(TestTriggerRx::Body, HBox::new(4, 4)),
+        ));
+        app.world_mut().spawn((
+            Pos::new(-20, 10),
+            PlatformerController::default(),
+            DynoBody::default(),
+            StaticRx::single(StaticRxKind::Default, HBox::new(4, 6)),
+        ));
+        body
+    }
+
+    /// Everything that should come out the same, bit for bit
+    fn state(app: &mut App) -> Vec<(Entity, Fx, Fx, Option<FVec2>, Option<u32>)> {
+        let mut q = app
+            .world_mut()
+            .query::<(Entity, &Pos, Option<&Dyno>, Option<&Contacts>)>();
+        let mut result = q
+            .iter(app.world())
+            .map(|(eid, pos, dyno, contacts)| {
+                (
+                    eid,
+                    pos.x,
+                    pos.y,
+                    dyno.map(|dyno| dyno.vel),
+                    contacts.and_then(|contacts| contacts.ground().map(|ground| ground.frames)),
+                )
+            })
+            .collect::<Vec<_>>();
+        result.sort_by_key(|(eid, ..)| *eid);
+        result
+    }
+
+    #[test]
+    fn replaying_from_a_restore_is_bit_exact() {
+        let mut app = test_app(TestSettings::default().with_gravity(512));
+        let body = spawn_world(&mut app);
+        step(&mut app, 20);
+        // A pending impulse only lives in the `DynoBody` until the next step
+        app.world_mut()
+            .get_mut::<DynoBody>(body)
+            .unwrap()
+            .add_impulse(FVec2::new(0, 100));
+        let snapshot = TestSnapshot::take(app.world_mut());
+
+        let mut first_run = vec![];
+        for _ in 0..30 {
+            step(&mut app, 1);
+            first_run.push(state(&mut app));
+        }
+        snapshot.restore(app.world_mut());
+        for (frame, expected) in first_run.into_iter().enumerate() {
+            step(&mut app, 1);
+            assert_eq!(state(&mut app), expected, "diverged on frame {frame}");
+        }
+    }
+
+    #[test]
+    fn rewind_goes_back_to_the_recorded_step() {
+        let mut app = test_app(TestSettings::default().with_gravity(512));
+        spawn_world(&mut app);
+        app.world_mut()
+            .resource_mut::<PhysicsHistoryGeneric<TestTriggerRx, TestTriggerTx>>()
+            .set_capacity(10);
+        step(&mut app, 5);
+        let at_five = state(&mut app);
+        step(&mut app, 3);
+        app.world_mut().trigger(RewindPhysics::new(3));
+        app.world_mut().flush();
+        assert_eq!(state(&mut app), at_five);
+    }
+}
@@ -1,4 +1,8 @@
-use std::marker::PhantomData;
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 use bevy::{ecs::lifecycle::HookContext, prelude::*};
 
@@ -17,6 +21,57 @@ impl SpatHashKind for SpatHashTriggerTx {}
 #[derive(Component, Clone, Reflect, Debug)]
 pub(crate) struct SpatHashOccludeLight;
 impl SpatHashKind for SpatHashOccludeLight {}
+/// Only ever used by short-lived hashes (see `carry::RxIndex`), rxs move too much to keep one around
+#[derive(Component, Clone, Reflect, Debug)]
+pub(crate) struct SpatHashStaticRx;
+impl SpatHashKind for SpatHashStaticRx {}
+
+/// How a broadphase finds the things that might be touching
+#[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq)]
+pub enum Broadphase {
+    /// Uniform grid of square cells. Works best when most things are smaller than a cell,
+    /// and huge things don't take up too many cells.
+    Grid { cell_size: i32 },
+    /// Everything sorted by left edge. No cell size to tune, so it copes with wildly different
+    /// sizes (huge solids next to tiny particles), and inserts/removes/queries are all logarithmic
+    /// plus whatever is nearby. Queries look back as far as the widest thing, so prefer it when
+    /// things are spread out more horizontally than they are wide.
+    SweepAndPrune,
+}
+impl Default for Broadphase {
+    fn default() -> Self {
+        Self::Grid { cell_size: 32 }
+    }
+}
+
+/// How one broadphase has been doing. Queries and candidates are counted since the last frame.
+#[derive(Clone, Copy, Debug, Default, Reflect)]
+pub struct BroadphaseKindStats {
+    /// How many things are in the broadphase
+    pub entities: usize,
+    /// How many grid cells have something in them (always zero for sweep-and-prune)
+    pub cells_occupied: usize,
+    /// How many times the broadphase was asked for candidates
+    pub queries: u32,
+    /// How many candidates it handed back in total
+    pub candidates: u32,
+}
+impl BroadphaseKindStats {
+    pub fn candidates_per_query(&self) -> f32 {
+        if self.queries == 0 {
+            return 0.0;
+        }
+        self.candidates as f32 / self.queries as f32
+    }
+}
+
+/// Stats for every broadphase, refreshed once a frame. Handy for tuning `Broadphase`s.
+#[derive(Resource, Clone, Debug, Default, Reflect)]
+pub struct BroadphaseStats {
+    pub static_tx: BroadphaseKindStats,
+    pub trigger_tx: BroadphaseKindStats,
+    pub occlude_light: BroadphaseKindStats,
+}
 
 #[derive(Copy, Clone, PartialEq, Eq, std::hash::Hash, Debug, Reflect)]
 pub(crate) struct SpatKey {
@@ -29,37 +84,75 @@ impl SpatKey {
     }
 }
 
+/// Where something lives in a broadphase.
+/// Grids use the cells, sweep-and-prune just uses the (rounded outwards) bounds.
 #[derive(Component, Clone, Reflect, Debug)]
 #[component(on_remove = on_remove_spat_hash::<K>)]
 pub(crate) struct SpatKeys<K: SpatHashKind> {
     keys: HashSet<SpatKey>,
+    bounds: Option<(IVec2, IVec2)>,
     _pd: Option<K>,
 }
 impl<K: SpatHashKind> SpatKeys<K> {
-    fn new(keys: HashSet<SpatKey>) -> Self {
-        Self { keys, _pd: None }
+    fn new(keys: HashSet<SpatKey>, bounds: Option<(IVec2, IVec2)>) -> Self {
+        Self {
+            keys,
+            bounds,
+            _pd: None,
+        }
     }
     pub(crate) fn iter(&self) -> bevy::platform::collections::hash_set::Iter<'_, SpatKey> {
         self.keys.iter()
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+struct SweepEntry {
+    min: IVec2,
+    max: IVec2,
+    eid: Entity,
+}
+
 #[derive(Resource, Reflect)]
 pub(crate) struct SpatHash<K: SpatHashKind> {
-    denominator: i32,
+    broadphase: Broadphase,
     map: HashMap<SpatKey, HashSet<Entity>>,
+    /// Only used by sweep-and-prune. Entries keyed by their `min.x`.
+    #[reflect(ignore)]
+    sorted: BTreeMap<i32, Vec<SweepEntry>>,
+    /// Only used by sweep-and-prune. How many entries there are of each width, so queries know
+    /// how far left something overlapping them could start.
+    #[reflect(ignore)]
+    widths: BTreeMap<i32, u32>,
+    num_entities: usize,
+    #[reflect(ignore)]
+    num_queries: AtomicU32,
+    #[reflect(ignore)]
+    num_candidates: AtomicU32,
     _pd: PhantomData<K>,
 }
 impl<K: SpatHashKind> Default for SpatHash<K> {
     fn default() -> Self {
+        Self::new(default())
+    }
+}
+impl<K: SpatHashKind> SpatHash<K> {
+    pub(crate) fn new(broadphase: Broadphase) -> Self {
+        if let Broadphase::Grid { cell_size } = broadphase {
+            debug_assert!(cell_size > 0);
+        }
         Self {
-            denominator: 32,
+            broadphase,
             map: default(),
+            sorted: default(),
+            widths: default(),
+            num_entities: 0,
+            num_queries: default(),
+            num_candidates: default(),
             _pd: default(),
         }
     }
-}
-impl<K: SpatHashKind> SpatHash<K> {
+
     fn inner_insert(&mut self, key: SpatKey, eid: Entity) {
         if !self.map.contains_key(&key) {
             self.map.insert(key, default());
@@ -77,28 +170,58 @@ impl<K: SpatHashKind> SpatHash<K> {
             self.map.remove(&key);
         }
     }
+    fn sweep_insert(&mut self, entry: SweepEntry) {
+        *self
+            .widths
+            .entry(entry.max.x.saturating_sub(entry.min.x))
+            .or_default() += 1;
+        self.sorted.entry(entry.min.x).or_default().push(entry);
+    }
+    fn sweep_remove(&mut self, min_x: i32, eid: Entity) {
+        let Some(entries) = self.sorted.get_mut(&min_x) else {
+            return;
+        };
+        let Some(ix) = entries.iter().position(|entry| entry.eid == eid) else {
+            return;
+        };
+        let entry = entries.swap_remove(ix);
+        if entries.is_empty() {
+            self.sorted.remove(&min_x);
+        }
+        let width = entry.max.x.saturating_sub(entry.min.x);
+        if let Some(count) = self.widths.get_mut(&width) {
+            *count -= 1;
+            if *count == 0 {
+                self.widths.remove(&width);
+            }
+        }
+    }
 }
 impl<K: SpatHashKind> SpatHash<K> {
     pub fn get_thbox_keys(&self, hbox: HBox) -> HashSet<SpatKey> {
+        self.get_bounds_keys(
+            FVec2::new(hbox.min_x(), hbox.min_y()),
+            FVec2::new(hbox.max_x(), hbox.max_y()),
+        )
+    }
+
+    /// Gets the keys covering an arbitrary world-space rectangle.
+    /// Degenerate (zero width or height) rectangles still get the keys they sit on.
+    pub fn get_bounds_keys(&self, min: FVec2, max: FVec2) -> HashSet<SpatKey> {
+        let Broadphase::Grid { cell_size } = self.broadphase else {
+            return default();
+        };
         let (min_x, max_x) = (
-            (hbox.min_x() / fx!(self.denominator))
-                .floor()
-                .to_num::<i32>(),
-            (hbox.max_x() / fx!(self.denominator))
-                .ceil()
-                .to_num::<i32>(),
+            (min.x / fx!(cell_size)).floor().to_num::<i32>(),
+            (max.x / fx!(cell_size)).ceil().to_num::<i32>(),
         );
         let (min_y, max_y) = (
-            (hbox.min_y() / fx!(self.denominator))
-                .floor()
-                .to_num::<i32>(),
-            (hbox.max_y() / fx!(self.denominator))
-                .ceil()
-                .to_num::<i32>(),
+            (min.y / fx!(cell_size)).floor().to_num::<i32>(),
+            (max.y / fx!(cell_size)).ceil().to_num::<i32>(),
         );
         let mut result = HashSet::default();
-        for x in min_x..max_x {
-            for y in min_y..max_y {
+        for x in min_x..max_x.max(min_x + 1) {
+            for y in min_y..max_y.max(min_y + 1) {
                 result.insert(SpatKey::new(x, y));
             }
         }
@@ -106,28 +229,89 @@ impl<K: SpatHashKind> SpatHash<K> {
     }
 
     pub fn get_keys(&self, pos: Pos, hboxes: Vec<HBox>) -> SpatKeys<K> {
-        let hset = hboxes
+        let thboxes = hboxes
             .iter()
-            .map(|hbox| self.get_thbox_keys(hbox.translated(pos.as_fvec2())))
+            .map(|hbox| hbox.translated(pos.as_fvec2()))
+            .collect::<Vec<_>>();
+        let hset = thboxes
+            .iter()
+            .map(|thbox| self.get_thbox_keys(thbox.clone()))
             .flatten()
             .collect();
-        SpatKeys::new(hset)
+        let bounds = thboxes
+            .iter()
+            .map(|thbox| {
+                (
+                    FVec2::new(thbox.min_x(), thbox.min_y()),
+                    FVec2::new(thbox.max_x(), thbox.max_y()),
+                )
+            })
+            .reduce(|(a_min, a_max), (b_min, b_max)| {
+                (
+                    FVec2::new(a_min.x.min(b_min.x), a_min.y.min(b_min.y)),
+                    FVec2::new(a_max.x.max(b_max.x), a_max.y.max(b_max.y)),
+                )
+            })
+            .map(|(min, max)| Self::round_bounds(min, max));
+        SpatKeys::new(hset, bounds)
+    }
+
+    pub fn get_region_keys(&self, min: FVec2, max: FVec2) -> SpatKeys<K> {
+        SpatKeys::new(
+            self.get_bounds_keys(min, max),
+            Some(Self::round_bounds(min, max)),
+        )
+    }
+
+    /// Rounds outwards so sweep-and-prune never misses anything
+    fn round_bounds(min: FVec2, max: FVec2) -> (IVec2, IVec2) {
+        (
+            IVec2::new(min.x.floor().to_num(), min.y.floor().to_num()),
+            IVec2::new(max.x.ceil().to_num(), max.y.ceil().to_num()),
+        )
     }
 
     pub fn insert(&mut self, eid: Entity, pos: Pos, hboxes: Vec<HBox>) -> SpatKeys<K> {
         let keys = self.get_keys(pos, hboxes);
-        for key in keys.iter() {
-            self.inner_insert(*key, eid);
+        self.num_entities += 1;
+        match self.broadphase {
+            Broadphase::Grid { .. } => {
+                for key in keys.iter() {
+                    self.inner_insert(*key, eid);
+                }
+            }
+            Broadphase::SweepAndPrune => {
+                if let Some((min, max)) = keys.bounds {
+                    self.sweep_insert(SweepEntry { min, max, eid });
+                }
+            }
         }
         keys
     }
 
     pub fn remove(&mut self, eid: Entity, keys: &SpatKeys<K>) {
-        for key in keys.iter() {
-            self.inner_remove(*key, eid);
+        self.num_entities = self.num_entities.saturating_sub(1);
+        match self.broadphase {
+            Broadphase::Grid { .. } => {
+                for key in keys.iter() {
+                    self.inner_remove(*key, eid);
+                }
+            }
+            Broadphase::SweepAndPrune => {
+                if let Some((min, _)) = keys.bounds {
+                    self.sweep_remove(min.x, eid);
+                }
+            }
         }
     }
 
+    pub(crate) fn clear(&mut self) {
+        self.map.clear();
+        self.sorted.clear();
+        self.widths.clear();
+        self.num_entities = 0;
+    }
+
     // Sugar for remove + insert
     pub fn update(
         &mut self,
@@ -137,18 +321,47 @@ impl<K: SpatHashKind> SpatHash<K> {
         hboxes: Vec<HBox>,
     ) -> SpatKeys<K> {
         self.remove(eid, old_keys);
-        let new_keys = self.get_keys(pos, hboxes);
-        for key in new_keys.iter() {
-            self.inner_insert(*key, eid);
-        }
-        new_keys
+        self.insert(eid, pos, hboxes)
     }
 
     pub fn get_eids(&self, keys: SpatKeys<K>) -> HashSet<Entity> {
-        keys.iter()
-            .map(|key| self.map.get(key).cloned().unwrap_or_default())
-            .flatten()
-            .collect()
+        let result: HashSet<Entity> = match self.broadphase {
+            Broadphase::Grid { .. } => keys
+                .iter()
+                .map(|key| self.map.get(key).cloned().unwrap_or_default())
+                .flatten()
+                .collect(),
+            Broadphase::SweepAndPrune => match keys.bounds {
+                Some((min, max)) => {
+                    // Touching counts, same as sharing a cell would. Nothing can start further
+                    // left than our left edge minus the widest thing and still reach us.
+                    let widest = self.widths.last_key_value().map_or(0, |(width, _)| *width);
+                    self.sorted
+                        .range(min.x.saturating_sub(widest)..=max.x)
+                        .flat_map(|(_, entries)| entries)
+                        .filter(|entry| {
+                            min.x <= entry.max.x && min.y <= entry.max.y && entry.min.y <= max.y
+                        })
+                        .map(|entry| entry.eid)
+                        .collect()
+                }
+                None => default(),
+            },
+        };
+        self.num_queries.fetch_add(1, Ordering::Relaxed);
+        self.num_candidates
+            .fetch_add(result.len() as u32, Ordering::Relaxed);
+        result
+    }
+
+    /// Gets the stats, resetting the per-frame counters
+    fn take_stats(&self) -> BroadphaseKindStats {
+        BroadphaseKindStats {
+            entities: self.num_entities,
+            cells_occupied: self.map.len(),
+            queries: self.num_queries.swap(0, Ordering::Relaxed),
+            candidates: self.num_candidates.swap(0, Ordering::Relaxed),
+        }
     }
 }
 
@@ -162,11 +375,90 @@ pub(crate) fn on_remove_spat_hash<K: SpatHashKind>(
         .remove(hook.entity, &keys);
 }
 
-pub(super) fn register_spat_hash(app: &mut App) {
+fn update_broadphase_stats(
+    mut stats: ResMut<BroadphaseStats>,
+    spat_hash_stx: Res<SpatHash<SpatHashStaticTx>>,
+    spat_hash_ttx: Res<SpatHash<SpatHashTriggerTx>>,
+    spat_hash_occlude_light: Res<SpatHash<SpatHashOccludeLight>>,
+) {
+    stats.static_tx = spat_hash_stx.take_stats();
+    stats.trigger_tx = spat_hash_ttx.take_stats();
+    stats.occlude_light = spat_hash_occlude_light.take_stats();
+}
+
+pub(super) fn register_spat_hash(
+    app: &mut App,
+    static_tx: Broadphase,
+    trigger_tx: Broadphase,
+    occlude_light: Broadphase,
+) {
     app.register_type::<SpatKeys<SpatHashStaticTx>>();
-    app.insert_resource(SpatHash::<SpatHashStaticTx>::default());
+    app.insert_resource(SpatHash::<SpatHashStaticTx>::new(static_tx));
     app.register_type::<SpatKeys<SpatHashTriggerTx>>();
-    app.insert_resource(SpatHash::<SpatHashTriggerTx>::default());
+    app.insert_resource(SpatHash::<SpatHashTriggerTx>::new(trigger_tx));
     app.register_type::<SpatKeys<SpatHashOccludeLight>>();
-    app.insert_resource(SpatHash::<SpatHashOccludeLight>::default());
+    app.insert_resource(SpatHash::<SpatHashOccludeLight>::new(occlude_light));
+    app.insert_resource(BroadphaseStats::default());
+    app.add_systems(Last, update_broadphase_stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn random_hbox(rng: &mut StdRng) -> (Pos, HBox) {
+        // Mostly small things, with the odd huge one
+        let (w, h) = if rng.gen_bool(0.05) {
+            (rng.gen_range(100..400), rng.gen_range(1..40))
+        } else {
+            (rng.gen_range(1..12), rng.gen_range(1..12))
+        };
+        let pos = Pos::new(rng.gen_range(-500..500i32), rng.gen_range(-500..500i32));
+        (pos, HBox::new(w, h))
+    }
+
+    /// Checks both broadphases against a brute force search while things get added, moved and removed
+    #[test]
+    fn broadphases_never_miss_anything() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut grid = SpatHash::<SpatHashStaticTx>::new(Broadphase::Grid { cell_size: 16 });
+        let mut sap = SpatHash::<SpatHashStaticTx>::new(Broadphase::SweepAndPrune);
+        let mut world = World::new();
+        let mut live = vec![];
+        for _ in 0..300 {
+            let eid = world.spawn_empty().id();
+            let (pos, hbox) = random_hbox(&mut rng);
+            let grid_keys = grid.insert(eid, pos, vec![hbox.clone()]);
+            let sap_keys = sap.insert(eid, pos, vec![hbox.clone()]);
+            live.push((eid, hbox.translated(pos.as_fvec2()), grid_keys, sap_keys));
+        }
+        for ix in (0..live.len()).rev().step_by(3) {
+            let (eid, _, grid_keys, sap_keys) = live.swap_remove(ix);
+            grid.remove(eid, &grid_keys);
+            sap.remove(eid, &sap_keys);
+        }
+        for (eid, thbox, grid_keys, sap_keys) in live.iter_mut().step_by(2) {
+            let (pos, hbox) = random_hbox(&mut rng);
+            *grid_keys = grid.update(*eid, grid_keys, pos, vec![hbox.clone()]);
+            *sap_keys = sap.update(*eid, sap_keys, pos, vec![hbox.clone()]);
+            *thbox = hbox.translated(pos.as_fvec2());
+        }
+        assert_eq!(sap.take_stats().entities, live.len());
+
+        for _ in 0..200 {
+            let (pos, query) = random_hbox(&mut rng);
+            let query = query.translated(pos.as_fvec2());
+            let expected = live
+                .iter()
+                .filter(|(_, thbox, ..)| thbox.overlaps_with(&query))
+                .map(|(eid, ..)| *eid)
+                .collect::<HashSet<_>>();
+            for hash in [&grid, &sap] {
+                let found = hash.get_eids(hash.get_keys(Pos::default(), vec![query.clone()]));
+                assert!(found.is_superset(&expected));
+            }
+        }
+    }
 }
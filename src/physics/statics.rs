@@ -1,12 +1,23 @@
-use bevy::{ecs::lifecycle::HookContext, prelude::*};
+use bevy::{
+    ecs::{lifecycle::HookContext, system::SystemParam},
+    prelude::*,
+};
+use fixed::traits::ToFixed;
 
 use crate::{
-    glue::Fx,
-    physics::{colls::CollKey, hbox::HBox, pos::Pos},
+    fx,
+    glue::{fvec::FVec2, Fx},
+    physics::{
+        colls::CollKey,
+        contacts::Contacts,
+        hbox::{HBox, HBoxMarker},
+        layers::PhysicsLayers,
+        pos::Pos,
+    },
     prelude::OccludeLight,
 };
 
-use super::spat_hash::{on_remove_spat_hash, SpatHash, SpatHashStaticTx};
+use super::spat_hash::{on_remove_spat_hash, SpatHash, SpatHashStaticTx, SpatKeys};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
 pub enum StaticRxKind {
@@ -23,16 +34,46 @@ pub enum StaticTxKind {
     Solid,
     /// Will let you go up, but not down
     PassUp,
+    /// Will let you go down, but not up
+    PassDown,
+    /// Will let you go left, but not right
+    PassLeft,
+    /// Will let you go right, but not left
+    PassRight,
+}
+impl StaticTxKind {
+    /// For pass-through kinds, the normal of the one side that actually stops stuff
+    pub fn solid_side(&self) -> Option<FVec2> {
+        match self {
+            Self::Solid => None,
+            Self::PassUp => Some(FVec2::Y),
+            Self::PassDown => Some(FVec2::new(0, -1)),
+            Self::PassLeft => Some(FVec2::new(-1, 0)),
+            Self::PassRight => Some(FVec2::X),
+        }
+    }
+    pub fn is_pass_through(&self) -> bool {
+        self.solid_side().is_some()
+    }
 }
 
+#[derive(Clone)]
 pub(crate) struct StaticRxComp {
     pub(crate) kind: StaticRxKind,
     pub(crate) hbox: HBox,
+    pub(crate) layers: PhysicsLayers,
 }
-#[derive(Component)]
+#[derive(Component, Clone)]
+#[require(Contacts)]
 pub struct StaticRx {
     pub(crate) comps: Vec<StaticRxComp>,
     pub coll_keys: Vec<CollKey>,
+    /// When true, moving StaticTxs will also carry this rx when it's flush against their sides
+    pub(crate) side_carry: bool,
+    /// While non-zero, ALL pass-through txs are ignored. Counts down every physics frame.
+    pub(crate) pass_through_frames: u32,
+    /// Specific pass-through txs that are ignored until we stop overlapping them
+    pub(crate) pass_through_ignored: Vec<Entity>,
 }
 impl StaticRx {
     pub fn single(kind: StaticRxKind, hbox: HBox) -> Self {
@@ -42,9 +83,75 @@ impl StaticRx {
         Self {
             comps: data
                 .into_iter()
-                .map(|(kind, hbox)| StaticRxComp { kind, hbox })
+                .map(|(kind, hbox)| StaticRxComp {
+                    kind,
+                    hbox,
+                    layers: default(),
+                })
                 .collect(),
             coll_keys: vec![],
+            side_carry: false,
+            pass_through_frames: 0,
+            pass_through_ignored: vec![],
+        }
+    }
+    pub fn with_side_carry(mut self, side_carry: bool) -> Self {
+        self.side_carry = side_carry;
+        self
+    }
+    /// Ignore all pass-through txs for the next `frames` physics frames (press down to drop, etc.)
+    pub fn drop_through(&mut self, frames: u32) {
+        self.pass_through_frames = self.pass_through_frames.max(frames);
+    }
+    /// Ignore a specific pass-through tx until we're no longer overlapping it
+    pub fn ignore_pass_through(&mut self, tx_ctrl: Entity) {
+        if !self.pass_through_ignored.contains(&tx_ctrl) {
+            self.pass_through_ignored.push(tx_ctrl);
+        }
+    }
+    /// Whether collisions with this pass-through tx are currently being ignored
+    pub fn is_ignoring_pass_through(&self, tx_ctrl: Entity) -> bool {
+        self.pass_through_frames > 0 || self.pass_through_ignored.contains(&tx_ctrl)
+    }
+    /// Sets the layers of every comp
+    pub fn with_layers(mut self, layers: PhysicsLayers) -> Self {
+        for comp in &mut self.comps {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Sets the layers of the comps whose hbox has the given marker
+    pub fn with_marker_layers(mut self, marker: HBoxMarker, layers: PhysicsLayers) -> Self {
+        for comp in self
+            .comps
+            .iter_mut()
+            .filter(|comp| comp.hbox.get_marker() == marker)
+        {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Swaps out all the hboxes (crouching, etc.). Comps keep their layers if an old comp had the same marker.
+    /// NOTE: Growing into something just gets resolved like any other overlap on the next physics step.
+    pub fn set_hboxes<I: IntoIterator<Item = (StaticRxKind, HBox)>>(&mut self, data: I) {
+        let old_comps = std::mem::take(&mut self.comps);
+        self.comps = data
+            .into_iter()
+            .map(|(kind, hbox)| StaticRxComp {
+                kind,
+                layers: old_comps
+                    .iter()
+                    .find(|old| old.hbox.get_marker() == hbox.get_marker())
+                    .map(|old| old.layers)
+                    .unwrap_or_default(),
+                hbox,
+            })
+            .collect();
+    }
+    /// Tweaks every hbox in place, keeping kinds and layers
+    pub fn update_hboxes<F: FnMut(&mut HBox)>(&mut self, mut f: F) {
+        for comp in &mut self.comps {
+            f(&mut comp.hbox);
         }
     }
     pub fn get_thboxes(&self, pos: Pos) -> Vec<HBox> {
@@ -55,17 +162,20 @@ impl StaticRx {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct StaticTxComp {
     pub(crate) kind: StaticTxKind,
     pub(crate) hbox: HBox,
+    pub(crate) layers: PhysicsLayers,
 }
-#[derive(Component, Debug)]
+#[derive(Component, Clone, Debug)]
 #[component(on_add = on_add_static_tx)]
 #[component(on_remove = on_remove_static_tx)]
 pub struct StaticTx {
     pub(crate) comps: Vec<StaticTxComp>,
     pub coll_keys: Vec<CollKey>,
+    /// Horizontal speed given to anything riding on top, on top of any actual movement (conveyors)
+    pub(crate) surface_vel: Fx,
 }
 fn on_add_static_tx(mut world: bevy::ecs::world::DeferredWorld, hook: HookContext) {
     let pos = world
@@ -101,9 +211,78 @@ impl StaticTx {
         Self {
             comps: data
                 .into_iter()
-                .map(|(kind, hbox)| StaticTxComp { kind, hbox })
+                .map(|(kind, hbox)| StaticTxComp {
+                    kind,
+                    hbox,
+                    layers: default(),
+                })
                 .collect(),
             coll_keys: vec![],
+            surface_vel: Fx::ZERO,
+        }
+    }
+    pub fn with_surface_vel<V: ToFixed>(mut self, surface_vel: V) -> Self {
+        self.surface_vel = fx!(surface_vel);
+        self
+    }
+    /// Sets the layers of every comp
+    pub fn with_layers(mut self, layers: PhysicsLayers) -> Self {
+        for comp in &mut self.comps {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Sets the layers of the comps whose hbox has the given marker
+    pub fn with_marker_layers(mut self, marker: HBoxMarker, layers: PhysicsLayers) -> Self {
+        for comp in self
+            .comps
+            .iter_mut()
+            .filter(|comp| comp.hbox.get_marker() == marker)
+        {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// A copy of this tx with every hbox swapped out for some new hboxes (keeping kinds, layers, etc.)
+    pub(crate) fn reshaped<F: Fn(&HBox) -> Vec<HBox>>(&self, f: F) -> Self {
+        Self {
+            comps: self
+                .comps
+                .iter()
+                .flat_map(|comp| {
+                    f(&comp.hbox).into_iter().map(|hbox| StaticTxComp {
+                        kind: comp.kind,
+                        hbox,
+                        layers: comp.layers,
+                    })
+                })
+                .collect(),
+            coll_keys: vec![],
+            surface_vel: self.surface_vel,
+        }
+    }
+    /// Swaps out all the hboxes. Comps keep their layers if an old comp had the same marker.
+    /// NOTE: Doesn't re-hash, go through `StaticTxHBoxes` for that.
+    pub(crate) fn set_hboxes<I: IntoIterator<Item = (StaticTxKind, HBox)>>(&mut self, data: I) {
+        let old_comps = std::mem::take(&mut self.comps);
+        self.comps = data
+            .into_iter()
+            .map(|(kind, hbox)| StaticTxComp {
+                kind,
+                layers: old_comps
+                    .iter()
+                    .find(|old| old.hbox.get_marker() == hbox.get_marker())
+                    .map(|old| old.layers)
+                    .unwrap_or_default(),
+                hbox,
+            })
+            .collect();
+    }
+    /// Tweaks every hbox in place, keeping kinds and layers.
+    /// NOTE: Doesn't re-hash, go through `StaticTxHBoxes` for that.
+    pub(crate) fn update_hboxes<F: FnMut(&mut HBox)>(&mut self, mut f: F) {
+        for comp in &mut self.comps {
+            f(&mut comp.hbox);
         }
     }
     pub fn get_thboxes(&self, pos: Pos) -> Vec<HBox> {
@@ -113,3 +292,48 @@ impl StaticTx {
             .collect()
     }
 }
+
+/// The way to change the hboxes of a `StaticTx` (growing hazards, shrinking platforms, etc.).
+/// The spatial hash (and any `OccludeLight::StaticTx`) is updated right away, so queries,
+/// teleports and moves later in the same frame see the new shape.
+#[derive(SystemParam)]
+pub struct StaticTxHBoxes<'w, 's> {
+    stx_q: Query<
+        'w,
+        's,
+        (
+            &'static Pos,
+            &'static mut StaticTx,
+            &'static mut SpatKeys<SpatHashStaticTx>,
+            Option<&'static mut OccludeLight>,
+        ),
+    >,
+    spat_hash: ResMut<'w, SpatHash<SpatHashStaticTx>>,
+}
+impl StaticTxHBoxes<'_, '_> {
+    /// Swaps out all the hboxes. Comps keep their layers if an old comp had the same marker.
+    /// Does nothing if `eid` has no (hashed) `StaticTx`.
+    pub fn set<I: IntoIterator<Item = (StaticTxKind, HBox)>>(&mut self, eid: Entity, data: I) {
+        self.edit(eid, |stx| stx.set_hboxes(data));
+    }
+    /// Tweaks every hbox in place, keeping kinds and layers.
+    /// Does nothing if `eid` has no (hashed) `StaticTx`.
+    pub fn update<F: FnMut(&mut HBox)>(&mut self, eid: Entity, f: F) {
+        self.edit(eid, |stx| stx.update_hboxes(f));
+    }
+    /// Runs `f` on the tx and re-hashes it. Does nothing if `eid` has no (hashed) tx.
+    pub(crate) fn edit<F: FnOnce(&mut StaticTx)>(&mut self, eid: Entity, f: F) {
+        let Ok((pos, mut stx, mut spat_keys, occlude_light)) = self.stx_q.get_mut(eid) else {
+            return;
+        };
+        f(&mut stx);
+        let hboxes = stx.comps.iter().map(|c| c.hbox.clone()).collect();
+        *spat_keys = self.spat_hash.update(eid, &spat_keys, *pos, hboxes);
+        // Light occlusion only re-hashes when it sees a change
+        if let Some(mut occlude_light) = occlude_light {
+            if matches!(*occlude_light, OccludeLight::StaticTx) {
+                occlude_light.set_changed();
+            }
+        }
+    }
+}
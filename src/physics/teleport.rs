@@ -0,0 +1,330 @@
+//! Moving things without going through the space in between (respawns, portals, level transitions).
+//! Writing to `Pos` directly leaves the spatial hashes and interpolation behind until the next step,
+//! so use this instead.
+
+use bevy::prelude::*;
+use fixed::traits::ToFixed;
+
+use crate::{
+    fx,
+    glue::{fvec::FVec2, Fx},
+    physics::{
+        pos::{Pos, RelPos},
+        spat_hash::{SpatHash, SpatHashStaticTx, SpatHashTriggerTx, SpatKeys},
+        statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind},
+        tiles::TileColliders,
+        timestep::PrevPos,
+        triggers::{TriggerKindTrait, TriggerTxGeneric},
+    },
+};
+
+/// How many times to try pushing out of solids before giving up
+const MAX_RESOLVE_ITERATIONS: usize = 4;
+
+/// Moves an entity (and everything hashed about it) to a new spot, at the end of the current command flush.
+/// Keeps velocity, and `z` unless `with_z` is used.
+/// Things with a `RelPos` get their offset moved by the same amount, so they stay where they're put.
+#[derive(EntityEvent, Clone, Debug)]
+pub struct Teleport {
+    pub entity: Entity,
+    pub to: FVec2,
+    /// If set, `z` changes too
+    pub z: Option<Fx>,
+    /// If set, a `StaticRx` gets pushed out of any solids at the destination.
+    /// If it can't get out, it doesn't move at all and `TeleportFailed` is triggered instead.
+    pub resolve: bool,
+}
+impl Teleport {
+    pub fn new<X: ToFixed, Y: ToFixed>(entity: Entity, x: X, y: Y) -> Self {
+        Self {
+            entity,
+            to: FVec2::new(x, y),
+            z: None,
+            resolve: false,
+        }
+    }
+    pub fn with_z<Z: ToFixed>(mut self, z: Z) -> Self {
+        self.z = Some(fx!(z));
+        self
+    }
+    pub fn with_resolve(mut self) -> Self {
+        self.resolve = true;
+        self
+    }
+}
+
+/// Triggered when a resolving `Teleport` would have put the entity inside a solid
+#[derive(EntityEvent, Clone, Debug)]
+pub struct TeleportFailed {
+    pub entity: Entity,
+    /// Where it was trying to go
+    pub to: FVec2,
+}
+
+/// Where the rx ends up after being pushed out of every solid it overlaps at `pos`, or `None` if
+/// it still overlaps something after a few tries (deep in a wall, or squeezed between two)
+fn resolve_overlap(world: &mut World, eid: Entity, mut pos: Pos) -> Option<Pos> {
+    let Some(srx) = world.get::<StaticRx>(eid).cloned() else {
+        return Some(pos);
+    };
+    let mut stx_q = world.query::<(&Pos, &StaticTx)>();
+    for _ in 0..=MAX_RESOLVE_ITERATIONS {
+        let mut push = None;
+        for comp in srx
+            .comps
+            .iter()
+            .filter(|comp| comp.kind != StaticRxKind::Observe)
+        {
+            let spat_hash = world.resource::<SpatHash<SpatHashStaticTx>>();
+            let keys = spat_hash.get_keys(pos, vec![comp.hbox.clone()]);
+            let mut candidates = spat_hash.get_eids(keys).into_iter().collect::<Vec<_>>();
+            candidates.sort();
+            let my_thbox = comp.hbox.translated(pos.as_fvec2());
+            for other_eid in candidates.into_iter().filter(|other| *other != eid) {
+                let Ok((tx_pos, stx)) = stx_q.get(world, other_eid) else {
+                    continue;
+                };
+                for tx_comp in stx.comps.iter().filter(|tx_comp| {
+                    tx_comp.kind == StaticTxKind::Solid
+                        && comp.layers.interacts_with(&tx_comp.layers)
+                }) {
+                    let tx_thbox = tx_comp.hbox.translated(tx_pos.as_fvec2());
+                    // Deal with the biggest overlap first, the rest usually sorts itself out
+                    if let Some(this_push) = my_thbox.get_push_out(&tx_thbox) {
+                        if push.is_none_or(|push: FVec2| {
+                            this_push.length_squared() > push.length_squared()
+                        }) {
+                            push = Some(this_push);
+                        }
+                    }
+                }
+            }
+            let tiles = world.resource::<TileColliders>();
+            for tile in tiles
+                .get_overlapping(&[my_thbox.clone()])
+                .into_iter()
+                .filter(|tile| {
+                    tile.kind == StaticTxKind::Solid && comp.layers.interacts_with(&tile.layers)
+                })
+            {
+                if let Some(this_push) = my_thbox.get_push_out(&tile.thbox) {
+                    if push.is_none_or(|push: FVec2| {
+                        this_push.length_squared() > push.length_squared()
+                    }) {
+                        push = Some(this_push);
+                    }
+                }
+            }
+        }
+        match push {
+            Some(push) if push != FVec2::ZERO => pos += push,
+            _ => return Some(pos),
+        }
+    }
+    None
+}
+
+fn teleport<TriggerTxKind: TriggerKindTrait>(world: &mut World, event: Teleport) {
+    let Some(old_pos) = world.get::<Pos>(event.entity).copied() else {
+        warn!("Can't Teleport {:?}, it has no Pos", event.entity);
+        return;
+    };
+    let mut new_pos = old_pos;
+    new_pos.x = event.to.x;
+    new_pos.y = event.to.y;
+    if let Some(z) = event.z {
+        new_pos.z = z;
+    }
+    if event.resolve {
+        match resolve_overlap(world, event.entity, new_pos) {
+            Some(resolved) => new_pos = resolved,
+            None => {
+                world.trigger(TeleportFailed {
+                    entity: event.entity,
+                    to: event.to,
+                });
+                return;
+            }
+        }
+    }
+
+    let mut ent = world.entity_mut(event.entity);
+    *ent.get_mut::<Pos>().expect("Pos checked above") = new_pos;
+    // Otherwise fixed timestep would smear it across the map for a frame
+    if let Some(mut prev) = ent.get_mut::<PrevPos>() {
+        prev.0 = new_pos;
+    }
+    // Otherwise the parent would pull it right back next step
+    let rel_pos = ent.get_mut::<RelPos>().map(|mut rel_pos| {
+        rel_pos.offset += new_pos.as_fvec2() - old_pos.as_fvec2();
+        *rel_pos
+    });
+    let parent_pos = ent
+        .get::<ChildOf>()
+        .map(|child_of| child_of.parent())
+        .and_then(|parent| world.get::<Pos>(parent).copied());
+    let mut ent = world.entity_mut(event.entity);
+    if let Some(mut tran) = ent.get_mut::<Transform>() {
+        match rel_pos {
+            Some(rel_pos) => rel_pos.write_transform(&new_pos, parent_pos.as_ref(), &mut tran),
+            None => {
+                tran.translation.x = new_pos.x.round().to_num();
+                tran.translation.y = new_pos.y.round().to_num();
+            }
+        }
+        tran.translation.z = new_pos.z.to_num();
+    }
+
+    // Light occlusion notices the `Pos` change on its own, but these get updated mid-step
+    macro_rules! rehash {
+        ($kind:ty, $ctrl:ty) => {{
+            let data = world.entity(event.entity).get::<$ctrl>().map(|ctrl| {
+                ctrl.comps
+                    .iter()
+                    .map(|comp| comp.hbox.clone())
+                    .collect::<Vec<_>>()
+            });
+            let old_keys = world.entity(event.entity).get::<SpatKeys<$kind>>().cloned();
+            if let (Some(hboxes), Some(old_keys)) = (data, old_keys) {
+                let new_keys = world.resource_mut::<SpatHash<$kind>>().update(
+                    event.entity,
+                    &old_keys,
+                    new_pos,
+                    hboxes,
+                );
+                world.entity_mut(event.entity).insert(new_keys);
+            }
+        }};
+    }
+    rehash!(SpatHashStaticTx, StaticTx);
+    rehash!(SpatHashTriggerTx, TriggerTxGeneric<TriggerTxKind>);
+}
+
+fn handle_teleport<TriggerTxKind: TriggerKindTrait>(trigger: On<Teleport>, mut commands: Commands) {
+    let event = trigger.event().clone();
+    commands.queue(move |world: &mut World| teleport::<TriggerTxKind>(world, event));
+}
+
+pub(super) fn register_teleport<TriggerTxKind: TriggerKindTrait>(app: &mut App) {
+    app.add_observer(handle_teleport::<TriggerTxKind>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{
+        hbox::HBox,
+        statics::StaticRxKind,
+        test_utils::{step, test_app},
+    };
+
+    #[derive(Resource, Default)]
+    struct Failed(Vec<(Entity, FVec2)>);
+
+    fn teleport_app() -> App {
+        let mut app = test_app(default());
+        app.init_resource::<Failed>();
+        app.add_observer(|trigger: On<TeleportFailed>, mut failed: ResMut<Failed>| {
+            failed.0.push((trigger.event().entity, trigger.event().to));
+        });
+        step(&mut app, 1);
+        app
+    }
+
+    fn teleport(app: &mut App, event: Teleport) {
+        app.world_mut().trigger(event);
+        app.world_mut().flush();
+    }
+
+    fn spawn_wall(app: &mut App, x: i32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(x, 0),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(16, 16)),
+            ))
+            .id()
+    }
+
+    fn spawn_body(app: &mut App) -> Entity {
+        app.world_mut()
+            .spawn((
+                Pos::new(100, 0),
+                StaticRx::single(StaticRxKind::Default, HBox::new(4, 4)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn resolving_pushes_out_of_solids_or_fails() {
+        let mut app = teleport_app();
+        spawn_wall(&mut app, 0);
+        // Leaves a pixel wide gap between the walls, too small for anything
+        spawn_wall(&mut app, 17);
+        let body = spawn_body(&mut app);
+        let pos_of = |app: &App| app.world().get::<Pos>(body).unwrap().as_fvec2();
+
+        // Half in the top of the wall, so out it goes
+        teleport(&mut app, Teleport::new(body, 6, 8).with_resolve());
+        assert_eq!(pos_of(&app), FVec2::new(6, 10));
+        assert!(app.world().resource::<Failed>().0.is_empty());
+
+        // Without resolving it goes exactly where it's told
+        teleport(&mut app, Teleport::new(body, 6, 0));
+        assert_eq!(pos_of(&app), FVec2::new(6, 0));
+
+        // Stuck bouncing between the walls, so it stays put
+        teleport(&mut app, Teleport::new(body, fx!(8.5), 0).with_resolve());
+        assert_eq!(pos_of(&app), FVec2::new(6, 0));
+        assert_eq!(
+            app.world().resource::<Failed>().0,
+            vec![(body, FVec2::new(fx!(8.5), 0))]
+        );
+    }
+
+    #[test]
+    fn teleporting_rehashes_and_keeps_z_unless_told() {
+        let mut app = teleport_app();
+        let wall = spawn_wall(&mut app, 0);
+        app.world_mut().get_mut::<Pos>(wall).unwrap().z = fx!(2);
+        let hbox = HBox::new(16, 16);
+        let hashed_at = |app: &App, x: i32, y: i32| {
+            let spat_hash = app.world().resource::<SpatHash<SpatHashStaticTx>>();
+            let keys = spat_hash.get_keys(Pos::new(x, y), vec![hbox.clone()]);
+            spat_hash.get_eids(keys).contains(&wall)
+        };
+        assert!(hashed_at(&app, 0, 0));
+
+        teleport(&mut app, Teleport::new(wall, 500, 500));
+        assert!(hashed_at(&app, 500, 500));
+        assert!(!hashed_at(&app, 0, 0));
+        assert_eq!(app.world().get::<Pos>(wall).unwrap().z, fx!(2));
+
+        teleport(&mut app, Teleport::new(wall, 500, 500).with_z(-1));
+        assert_eq!(app.world().get::<Pos>(wall).unwrap().z, fx!(-1));
+        assert_eq!(
+            app.world().get::<Transform>(wall).unwrap().translation.z,
+            -1.0
+        );
+    }
+
+    #[test]
+    fn teleporting_relative_things_moves_their_offset() {
+        let mut app = teleport_app();
+        let parent = spawn_body(&mut app);
+        let child = app
+            .world_mut()
+            .spawn((RelPos::new(10, 0), ChildOf(parent)))
+            .id();
+        step(&mut app, 1);
+        let pos_of = |app: &App| app.world().get::<Pos>(child).unwrap().as_fvec2();
+        assert_eq!(pos_of(&app), FVec2::new(110, 0));
+
+        teleport(&mut app, Teleport::new(child, 120, 5));
+        step(&mut app, 1);
+        assert_eq!(pos_of(&app), FVec2::new(120, 5));
+        assert_eq!(
+            app.world().get::<RelPos>(child).unwrap().offset,
+            FVec2::new(20, 5)
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Shared setup for the physics tests. Just the physics plugin, no window, no rendering.
+
+use bevy::prelude::*;
+
+use crate::{
+    fx,
+    glue::{bullet_time::BulletTime, Fx},
+    input::prelude::Input,
+    physics::{
+        plugin::{PhysicsPluginGeneric, PhysicsSettingsGeneric},
+        triggers::TriggerKindTrait,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+pub(crate) enum TestTriggerRx {
+    Body,
+}
+impl TriggerKindTrait for TestTriggerRx {}
+
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+pub(crate) enum TestTriggerTx {
+    Zone,
+}
+impl TriggerKindTrait for TestTriggerTx {}
+
+pub(crate) type TestSettings = PhysicsSettingsGeneric<TestTriggerRx, TestTriggerTx>;
+
+/// An app that steps physics by exactly 1/64th of a second every update.
+/// A power of two, so velocities like 64 move exactly a pixel a step.
+pub(crate) fn test_app(settings: TestSettings) -> App {
+    let mut app = App::new();
+    // Physics moves independent bodies with `par_iter`, which needs the task pools
+    app.add_plugins(TaskPoolPlugin::default());
+    app.insert_resource(BulletTime::with_delta_secs(fx!(1) / fx!(64)));
+    app.insert_resource(Input::default());
+    // The debug drawing is toggled by a key, so it needs keyboard input to exist
+    app.init_resource::<ButtonInput<KeyCode>>();
+    app.add_plugins(PhysicsPluginGeneric::new(settings));
+    app
+}
+
+/// Runs `steps` physics steps
+pub(crate) fn step(app: &mut App, steps: usize) {
+    for _ in 0..steps {
+        app.update();
+    }
+}
@@ -0,0 +1,235 @@
+//! Level geometry as grids of cells in a resource, instead of one `StaticTx` entity per cell.
+//! Loading a grid is just filling in a map, and finding what's near an hbox is just looking at the
+//! cells under it, so big levels don't pay for every collider in the spatial hash.
+//! Rxs collide with tiles exactly like they would with a `StaticTx` per cell (same kinds, same
+//! layers, same records), so moving platforms and other txs work alongside them.
+
+use bevy::{ecs::lifecycle::HookContext, prelude::*};
+use fixed::traits::ToFixed;
+
+use crate::{
+    glue::{fvec::FVec2, Fx},
+    physics::{hbox::HBox, layers::PhysicsLayers, pos::Pos, statics::StaticTxKind},
+    prelude::HashMap,
+};
+
+/// One grid of same-sized square cells. Every cell is either empty or a tx of some kind.
+#[derive(Clone, Debug)]
+pub struct TileGrid {
+    /// Bottom left corner of cell (0, 0)
+    origin: FVec2,
+    cell_size: u32,
+    layers: PhysicsLayers,
+    cells: HashMap<IVec2, StaticTxKind>,
+}
+impl TileGrid {
+    /// Panics if `cell_size` is zero
+    pub fn new(cell_size: u32) -> Self {
+        assert!(cell_size > 0, "tile cell_size must be positive");
+        Self {
+            origin: FVec2::ZERO,
+            cell_size,
+            layers: default(),
+            cells: default(),
+        }
+    }
+    pub fn with_origin<X: ToFixed, Y: ToFixed>(mut self, x: X, y: Y) -> Self {
+        self.origin = FVec2::new(x, y);
+        self
+    }
+    pub fn with_layers(mut self, layers: PhysicsLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+    pub fn get_cell_size(&self) -> u32 {
+        self.cell_size
+    }
+    pub fn get_layers(&self) -> PhysicsLayers {
+        self.layers
+    }
+    /// The cell a point is in
+    pub fn cell_at(&self, point: FVec2) -> IVec2 {
+        let size = Fx::from_num(self.cell_size);
+        IVec2::new(
+            ((point.x - self.origin.x) / size).floor().to_num(),
+            ((point.y - self.origin.y) / size).floor().to_num(),
+        )
+    }
+    /// The center of a cell
+    pub fn cell_center(&self, cell: IVec2) -> FVec2 {
+        let size = Fx::from_num(self.cell_size);
+        self.origin + FVec2::new(cell.x, cell.y) * size + FVec2::ONE * (size / 2)
+    }
+    pub fn get(&self, cell: IVec2) -> Option<StaticTxKind> {
+        self.cells.get(&cell).copied()
+    }
+    /// `None` empties the cell
+    pub fn set(&mut self, cell: IVec2, kind: Option<StaticTxKind>) {
+        match kind {
+            Some(kind) => {
+                self.cells.insert(cell, kind);
+            }
+            None => {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, StaticTxKind)> + '_ {
+        self.cells.iter().map(|(cell, kind)| (*cell, *kind))
+    }
+    /// Cells under the given region, sorted bottom to top, left to right
+    fn get_region(&self, min: FVec2, max: FVec2) -> Vec<(IVec2, StaticTxKind)> {
+        let (lo, hi) = (self.cell_at(min), self.cell_at(max));
+        // Widened first (and saturating), a region spanning most of the i32 range overflows otherwise
+        let area = (hi.x as i64 - lo.x as i64 + 1).saturating_mul(hi.y as i64 - lo.y as i64 + 1);
+        let mut result = if area <= self.cells.len() as i64 {
+            let mut result = vec![];
+            for y in lo.y..=hi.y {
+                for x in lo.x..=hi.x {
+                    let cell = IVec2::new(x, y);
+                    if let Some(kind) = self.get(cell) {
+                        result.push((cell, kind));
+                    }
+                }
+            }
+            result
+        } else {
+            // Huge regions (long casts, etc.) are faster to check the other way around
+            self.iter()
+                .filter(|(cell, _)| {
+                    lo.x <= cell.x && cell.x <= hi.x && lo.y <= cell.y && cell.y <= hi.y
+                })
+                .collect()
+        };
+        result.sort_by_key(|(cell, _)| (cell.y, cell.x));
+        result
+    }
+}
+
+/// A single cell, looking like any other static tx comp
+#[derive(Clone, Debug)]
+pub(crate) struct TileRef {
+    pub(crate) ctrl: Entity,
+    /// Center of the cell
+    pub(crate) pos: Pos,
+    pub(crate) kind: StaticTxKind,
+    pub(crate) layers: PhysicsLayers,
+    pub(crate) thbox: HBox,
+}
+
+/// Every tile grid, keyed by the entity that stands in as the tx ctrl of its cells (in coll
+/// records, cast hits, pass-through ignoring, etc.)
+#[derive(Resource, Clone, Debug, Default)]
+pub struct TileColliders {
+    grids: HashMap<Entity, TileGrid>,
+}
+impl TileColliders {
+    /// NOTE: Put `TileGridCtrl` on the ctrl too, so the grid goes away when it's despawned
+    pub fn insert_grid(&mut self, ctrl: Entity, grid: TileGrid) {
+        self.grids.insert(ctrl, grid);
+    }
+    pub fn remove_grid(&mut self, ctrl: Entity) -> Option<TileGrid> {
+        self.grids.remove(&ctrl)
+    }
+    pub fn get_grid(&self, ctrl: Entity) -> Option<&TileGrid> {
+        self.grids.get(&ctrl)
+    }
+    pub fn get_grid_mut(&mut self, ctrl: Entity) -> Option<&mut TileGrid> {
+        self.grids.get_mut(&ctrl)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.grids.is_empty()
+    }
+    /// Every tile in every grid, in no particular order
+    pub(crate) fn iter_tiles(&self) -> impl Iterator<Item = TileRef> + '_ {
+        self.grids.iter().flat_map(|(ctrl, grid)| {
+            grid.iter()
+                .map(move |(cell, kind)| make_ref(*ctrl, grid, cell, kind))
+        })
+    }
+    /// Every tile touching the region, sorted by ctrl then cell so things stay deterministic
+    pub(crate) fn get_region(&self, min: FVec2, max: FVec2) -> Vec<TileRef> {
+        let mut ctrls = self.grids.keys().copied().collect::<Vec<_>>();
+        ctrls.sort();
+        ctrls
+            .into_iter()
+            .flat_map(|ctrl| {
+                let grid = &self.grids[&ctrl];
+                grid.get_region(min, max)
+                    .into_iter()
+                    .map(move |(cell, kind)| make_ref(ctrl, grid, cell, kind))
+            })
+            .collect()
+    }
+    /// Every tile touching any of the given thboxes
+    pub(crate) fn get_overlapping(&self, thboxes: &[HBox]) -> Vec<TileRef> {
+        if self.grids.is_empty() || thboxes.is_empty() {
+            return vec![];
+        }
+        let (mut min, mut max) = (FVec2::new(Fx::MAX, Fx::MAX), FVec2::new(Fx::MIN, Fx::MIN));
+        for thbox in thboxes {
+            min = min.min(thbox.bottom_left());
+            max = max.max(thbox.top_right());
+        }
+        self.get_region(min, max)
+    }
+}
+
+fn make_ref(ctrl: Entity, grid: &TileGrid, cell: IVec2, kind: StaticTxKind) -> TileRef {
+    let center = grid.cell_center(cell);
+    TileRef {
+        ctrl,
+        pos: Pos::new(center.x, center.y),
+        kind,
+        layers: grid.layers,
+        thbox: HBox::new(grid.cell_size, grid.cell_size).translated(center),
+    }
+}
+
+/// Removes the ctrl's grid from `TileColliders` when it's removed (or despawned)
+#[derive(Component, Clone, Debug, Default)]
+#[component(on_remove = on_remove_tile_grid_ctrl)]
+pub struct TileGridCtrl;
+fn on_remove_tile_grid_ctrl(mut world: bevy::ecs::world::DeferredWorld, hook: HookContext) {
+    if let Some(mut tiles) = world.get_resource_mut::<TileColliders>() {
+        tiles.remove_grid(hook.entity);
+    }
+}
+
+pub(super) fn register_tiles(app: &mut App) {
+    app.insert_resource(TileColliders::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "cell_size must be positive")]
+    fn zero_cell_size_panics() {
+        TileGrid::new(0);
+    }
+
+    #[test]
+    fn huge_regions_just_walk_the_cells() {
+        let mut grid = TileGrid::new(1);
+        grid.set(IVec2::new(3, 0), Some(StaticTxKind::Solid));
+        grid.set(IVec2::new(-2, 0), Some(StaticTxKind::PassUp));
+        grid.set(IVec2::new(0, 5), Some(StaticTxKind::Solid));
+        let far = Fx::from_num(i32::MAX - 1);
+        assert_eq!(
+            grid.get_region(FVec2::new(-far, -far), FVec2::new(far, far)),
+            vec![
+                (IVec2::new(-2, 0), StaticTxKind::PassUp),
+                (IVec2::new(3, 0), StaticTxKind::Solid),
+                (IVec2::new(0, 5), StaticTxKind::Solid),
+            ]
+        );
+    }
+}
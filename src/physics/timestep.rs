@@ -0,0 +1,85 @@
+//! Physics can either step once per frame in `Update` (the default), or at a fixed tick rate in
+//! `FixedUpdate`. In fixed mode `Pos` is the authoritative tick state, and transforms get
+//! interpolated between the previous and current tick so high refresh rate displays look smooth.
+
+use bevy::prelude::*;
+
+use crate::{
+    fx,
+    glue::{bullet_time::BulletTime, Fx},
+    physics::{
+        plugin::PhysicsDefaults,
+        pos::{Pos, RelPos},
+    },
+};
+
+/// How much (bullet) time the physics should advance this step.
+/// Per-frame mode: the frame's bullet time delta.
+/// Fixed mode: one tick, scaled by the current bullet time factor.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PhysicsDelta(pub(crate) Fx);
+impl PhysicsDelta {
+    pub(crate) fn delta_secs(&self) -> Fx {
+        self.0
+    }
+}
+
+/// Where this thing was at the end of the previous fixed tick. Only maintained in fixed mode.
+#[derive(Component, Clone, Copy, Debug)]
+pub(crate) struct PrevPos(pub(crate) Pos);
+
+pub(super) fn update_physics_delta(
+    bullet_time: Res<BulletTime>,
+    physics_defaults: Res<PhysicsDefaults>,
+    mut physics_delta: ResMut<PhysicsDelta>,
+) {
+    physics_delta.0 = match physics_defaults.fixed_hz {
+        Some(hz) => fx!(1) / fx!(hz) * bullet_time.get_factor(),
+        None => bullet_time.delta_secs(),
+    };
+}
+
+/// Remembers where everything was before this tick moves it
+pub(super) fn store_prev_pos(
+    mut commands: Commands,
+    mut pos_q: Query<(Entity, &Pos, Option<&mut PrevPos>)>,
+) {
+    for (eid, pos, prev) in &mut pos_q {
+        match prev {
+            Some(mut prev) => prev.0 = *pos,
+            None => {
+                commands.entity(eid).insert(PrevPos(*pos));
+            }
+        }
+    }
+}
+
+/// Puts transforms between the last two ticks, based on how far we are into the next one.
+/// NOTE: Still rounds to whole pixels, it's just that the rounding happens every frame instead of every tick.
+pub(super) fn interpolate_transforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut ents: Query<(
+        &Pos,
+        Option<&PrevPos>,
+        Option<(&RelPos, &ChildOf)>,
+        &mut Transform,
+    )>,
+    parent_q: Query<&Pos>,
+) {
+    let alpha = fx!(fixed_time.overstep_fraction());
+    for (pos, prev, rel_pos, mut tran) in &mut ents {
+        // Follows the parent's interpolation for free
+        if let Some((rel_pos, child_of)) = rel_pos {
+            rel_pos.write_transform(pos, parent_q.get(child_of.parent()).ok(), &mut tran);
+            tran.translation.z = pos.z.to_num();
+            continue;
+        }
+        let shown = match prev {
+            Some(prev) => prev.0 + (pos.as_fvec2() - prev.0.as_fvec2()) * alpha,
+            None => *pos,
+        };
+        tran.translation.x = shown.x.round().to_num::<f32>();
+        tran.translation.y = shown.y.round().to_num::<f32>();
+        tran.translation.z = pos.z.to_num();
+    }
+}
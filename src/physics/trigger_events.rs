@@ -0,0 +1,236 @@
+//! Trigger colls are cleared every frame. This remembers which pairs were overlapping last frame
+//! so gameplay code can react to things starting/stopping overlapping without diffing records.
+
+use bevy::prelude::*;
+
+use crate::{
+    physics::{colls::TriggerCollsGeneric, hbox::HBoxMarker, triggers::TriggerKindTrait},
+    prelude::*,
+};
+
+macro_rules! defn_trigger_event {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        /// NOTE: Fired as both an observer event and a message. For exits, either ctrl may have
+        /// been despawned already.
+        #[derive(Event, Message, Clone, Debug)]
+        pub struct $name<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> {
+            /// Entity of the control associated with the rx
+            pub rx_ctrl: Entity,
+            /// The kind of the rx
+            pub rx_kind: TriggerRxKind,
+            /// The marker of the hbox on the rx
+            pub rx_hbox: HBoxMarker,
+            /// Entity of the control associated with the tx
+            pub tx_ctrl: Entity,
+            /// The kind of the tx
+            pub tx_kind: TriggerTxKind,
+            /// The marker of the hbox on the tx
+            pub tx_hbox: HBoxMarker,
+        }
+        impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait>
+            $name<TriggerRxKind, TriggerTxKind>
+        {
+            fn from_pair(key: &TriggerPairKey, kinds: &(TriggerRxKind, TriggerTxKind)) -> Self {
+                Self {
+                    rx_ctrl: key.rx_ctrl,
+                    rx_kind: kinds.0.clone(),
+                    rx_hbox: key.rx_hbox,
+                    tx_ctrl: key.tx_ctrl,
+                    tx_kind: kinds.1.clone(),
+                    tx_hbox: key.tx_hbox,
+                }
+            }
+        }
+    };
+}
+defn_trigger_event!(
+    /// A rx hbox started overlapping a tx hbox this frame
+    TriggerEnterGeneric
+);
+defn_trigger_event!(
+    /// A rx hbox was overlapping a tx hbox last frame, and still is
+    TriggerStayGeneric
+);
+defn_trigger_event!(
+    /// A rx hbox was overlapping a tx hbox last frame, and isn't anymore
+    TriggerExitGeneric
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, std::hash::Hash)]
+struct TriggerPairKey {
+    rx_ctrl: Entity,
+    rx_hbox: HBoxMarker,
+    tx_ctrl: Entity,
+    tx_hbox: HBoxMarker,
+}
+
+/// The pairs that were overlapping as of the last physics update.
+/// NOTE: Pairs are keyed by ctrls and markers, so hboxes on the same ctrl that share a marker count as one.
+#[derive(Resource, Clone)]
+pub(super) struct TriggerPairsGeneric<
+    TriggerRxKind: TriggerKindTrait,
+    TriggerTxKind: TriggerKindTrait,
+> {
+    map: HashMap<TriggerPairKey, (TriggerRxKind, TriggerTxKind)>,
+}
+impl<TriggerRxKind: TriggerKindTrait, TriggerTxKind: TriggerKindTrait> Default
+    for TriggerPairsGeneric<TriggerRxKind, TriggerTxKind>
+{
+    fn default() -> Self {
+        Self { map: default() }
+    }
+}
+
+pub(super) fn update_trigger_pairs<
+    TriggerRxKind: TriggerKindTrait,
+    TriggerTxKind: TriggerKindTrait,
+>(
+    trigger_colls: Res<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut pairs: ResMut<TriggerPairsGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut commands: Commands,
+    mut enter_writer: MessageWriter<TriggerEnterGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut stay_writer: MessageWriter<TriggerStayGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut exit_writer: MessageWriter<TriggerExitGeneric<TriggerRxKind, TriggerTxKind>>,
+) {
+    let mut this_frame = HashMap::<TriggerPairKey, (TriggerRxKind, TriggerTxKind)>::new();
+    for coll in trigger_colls.map.values() {
+        let key = TriggerPairKey {
+            rx_ctrl: coll.rx_ctrl,
+            rx_hbox: coll.rx_hbox,
+            tx_ctrl: coll.tx_ctrl,
+            tx_hbox: coll.tx_hbox,
+        };
+        this_frame
+            .entry(key)
+            .or_insert_with(|| (coll.rx_kind.clone(), coll.tx_kind.clone()));
+    }
+
+    // Sorted so the events come out in the same order every time
+    let mut exited = pairs
+        .map
+        .iter()
+        .filter(|(key, _)| !this_frame.contains_key(*key))
+        .collect::<Vec<_>>();
+    exited.sort_by_key(|(key, _)| **key);
+    for (key, kinds) in exited {
+        let event = TriggerExitGeneric::from_pair(key, kinds);
+        exit_writer.write(event.clone());
+        commands.trigger(event);
+    }
+
+    let mut current = this_frame.iter().collect::<Vec<_>>();
+    current.sort_by_key(|(key, _)| **key);
+    for (key, kinds) in current {
+        if pairs.map.contains_key(key) {
+            let event = TriggerStayGeneric::from_pair(key, kinds);
+            stay_writer.write(event.clone());
+            commands.trigger(event);
+        } else {
+            let event = TriggerEnterGeneric::from_pair(key, kinds);
+            enter_writer.write(event.clone());
+            commands.trigger(event);
+        }
+    }
+
+    pairs.map = this_frame;
+}
+
+pub(super) fn register_trigger_events<
+    TriggerRxKind: TriggerKindTrait,
+    TriggerTxKind: TriggerKindTrait,
+>(
+    app: &mut App,
+) {
+    app.insert_resource(TriggerPairsGeneric::<TriggerRxKind, TriggerTxKind>::default());
+    app.add_message::<TriggerEnterGeneric<TriggerRxKind, TriggerTxKind>>();
+    app.add_message::<TriggerStayGeneric<TriggerRxKind, TriggerTxKind>>();
+    app.add_message::<TriggerExitGeneric<TriggerRxKind, TriggerTxKind>>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fx,
+        physics::test_utils::{step, test_app, TestTriggerRx, TestTriggerTx},
+    };
+
+    type Enter = TriggerEnterGeneric<TestTriggerRx, TestTriggerTx>;
+    type Stay = TriggerStayGeneric<TestTriggerRx, TestTriggerTx>;
+    type Exit = TriggerExitGeneric<TestTriggerRx, TestTriggerTx>;
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<(&'static str, Entity, Entity)>);
+
+    #[test]
+    fn events_come_out_exits_first_then_by_pair() {
+        let mut app = test_app(default());
+        app.init_resource::<Log>();
+        app.add_observer(|ev: On<Enter>, mut log: ResMut<Log>| {
+            log.0.push(("enter", ev.rx_ctrl, ev.tx_ctrl))
+        });
+        app.add_observer(|ev: On<Stay>, mut log: ResMut<Log>| {
+            log.0.push(("stay", ev.rx_ctrl, ev.tx_ctrl))
+        });
+        app.add_observer(|ev: On<Exit>, mut log: ResMut<Log>| {
+            log.0.push(("exit", ev.rx_ctrl, ev.tx_ctrl))
+        });
+        let mut spawn_zone = |x: i32| {
+            app.world_mut()
+                .spawn((
+                    Pos::new(x, 0),
+                    TriggerTxGeneric::single(TestTriggerTx::Zone, HBox::new(8, 8)),
+                ))
+                .id()
+        };
+        let zone_a = spawn_zone(0);
+        let zone_b = spawn_zone(8);
+        let mut spawn_body = |x: i32, vel: i32| {
+            app.world_mut()
+                .spawn((
+                    Pos::new(x, 0),
+                    Dyno::new(fx!(vel), Fx::ZERO),
+                    TriggerRxGeneric::single(TestTriggerRx::Body, HBox::new(4, 4)),
+                ))
+                .id()
+        };
+        let sitter = spawn_body(0, 0);
+        // Two pixels a step, straddles both zones after one step and is only in b after three
+        // (anything touched along the way counts, so it's still in a on the way out)
+        let mover = spawn_body(2, 128);
+
+        // Within each kind, events are in pair order (which is `Entity`'s order, not spawn order)
+        let in_pair_order = |kind: &'static str, mut pairs: Vec<(Entity, Entity)>| {
+            pairs.sort();
+            pairs.into_iter().map(move |(rx, tx)| (kind, rx, tx))
+        };
+        step(&mut app, 1);
+        assert_eq!(
+            std::mem::take(&mut app.world_mut().resource_mut::<Log>().0),
+            in_pair_order(
+                "enter",
+                vec![(sitter, zone_a), (mover, zone_a), (mover, zone_b)]
+            )
+            .collect::<Vec<_>>()
+        );
+        step(&mut app, 1);
+        assert!(app
+            .world()
+            .resource::<Log>()
+            .0
+            .iter()
+            .all(|(kind, _, _)| *kind == "stay"));
+        app.world_mut().resource_mut::<Log>().0.clear();
+        step(&mut app, 1);
+        assert_eq!(
+            std::mem::take(&mut app.world_mut().resource_mut::<Log>().0),
+            in_pair_order("exit", vec![(mover, zone_a)])
+                .chain(in_pair_order(
+                    "stay",
+                    vec![(sitter, zone_a), (mover, zone_b)]
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,19 +1,29 @@
-use bevy::{ecs::lifecycle::HookContext, prelude::*};
+use bevy::{
+    ecs::{lifecycle::HookContext, system::SystemParam},
+    prelude::*,
+};
 
-use crate::physics::{colls::CollKey, hbox::HBox, pos::Pos};
+use crate::physics::{
+    colls::CollKey,
+    hbox::{HBox, HBoxMarker},
+    layers::PhysicsLayers,
+    pos::Pos,
+};
 
-use super::spat_hash::{on_remove_spat_hash, SpatHash, SpatHashTriggerTx};
+use super::spat_hash::{on_remove_spat_hash, SpatHash, SpatHashTriggerTx, SpatKeys};
 
 pub trait TriggerKindTrait:
     Clone + std::fmt::Debug + std::hash::Hash + std::marker::Send + std::marker::Sync + 'static
 {
 }
 
+#[derive(Clone)]
 pub(crate) struct TriggerRxComp<TriggerRxKind: TriggerKindTrait> {
     pub(crate) kind: TriggerRxKind,
     pub(crate) hbox: HBox,
+    pub(crate) layers: PhysicsLayers,
 }
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct TriggerRxGeneric<TriggerRxKind: TriggerKindTrait> {
     pub(crate) comps: Vec<TriggerRxComp<TriggerRxKind>>,
     pub coll_keys: Vec<CollKey>,
@@ -26,11 +36,55 @@ impl<TriggerRxKind: TriggerKindTrait> TriggerRxGeneric<TriggerRxKind> {
         Self {
             comps: data
                 .into_iter()
-                .map(|(kind, hbox)| TriggerRxComp { kind, hbox })
+                .map(|(kind, hbox)| TriggerRxComp {
+                    kind,
+                    hbox,
+                    layers: default(),
+                })
                 .collect(),
             coll_keys: vec![],
         }
     }
+    /// Sets the layers of every comp
+    pub fn with_layers(mut self, layers: PhysicsLayers) -> Self {
+        for comp in &mut self.comps {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Sets the layers of the comps whose hbox has the given marker
+    pub fn with_marker_layers(mut self, marker: HBoxMarker, layers: PhysicsLayers) -> Self {
+        for comp in self
+            .comps
+            .iter_mut()
+            .filter(|comp| comp.hbox.get_marker() == marker)
+        {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Swaps out all the hboxes. Comps keep their layers if an old comp had the same marker.
+    pub fn set_hboxes<I: IntoIterator<Item = (TriggerRxKind, HBox)>>(&mut self, data: I) {
+        let old_comps = std::mem::take(&mut self.comps);
+        self.comps = data
+            .into_iter()
+            .map(|(kind, hbox)| TriggerRxComp {
+                kind,
+                layers: old_comps
+                    .iter()
+                    .find(|old| old.hbox.get_marker() == hbox.get_marker())
+                    .map(|old| old.layers)
+                    .unwrap_or_default(),
+                hbox,
+            })
+            .collect();
+    }
+    /// Tweaks every hbox in place, keeping kinds and layers
+    pub fn update_hboxes<F: FnMut(&mut HBox)>(&mut self, mut f: F) {
+        for comp in &mut self.comps {
+            f(&mut comp.hbox);
+        }
+    }
     pub fn get_thboxes(&self, pos: Pos) -> Vec<HBox> {
         self.comps
             .iter()
@@ -39,11 +93,13 @@ impl<TriggerRxKind: TriggerKindTrait> TriggerRxGeneric<TriggerRxKind> {
     }
 }
 
+#[derive(Clone)]
 pub(crate) struct TriggerTxComp<TriggerTxKind: TriggerKindTrait> {
     pub(crate) kind: TriggerTxKind,
     pub(crate) hbox: HBox,
+    pub(crate) layers: PhysicsLayers,
 }
-#[derive(Component)]
+#[derive(Component, Clone)]
 #[component(on_add = on_add_trigger_tx::<TriggerTxKind>)]
 #[component(on_remove = on_remove_spat_hash::<SpatHashTriggerTx>)]
 pub struct TriggerTxGeneric<TriggerTxKind: TriggerKindTrait> {
@@ -78,11 +134,57 @@ impl<TriggerTxKind: TriggerKindTrait> TriggerTxGeneric<TriggerTxKind> {
         Self {
             comps: data
                 .into_iter()
-                .map(|(kind, hbox)| TriggerTxComp { kind, hbox })
+                .map(|(kind, hbox)| TriggerTxComp {
+                    kind,
+                    hbox,
+                    layers: default(),
+                })
                 .collect(),
             coll_keys: vec![],
         }
     }
+    /// Sets the layers of every comp
+    pub fn with_layers(mut self, layers: PhysicsLayers) -> Self {
+        for comp in &mut self.comps {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Sets the layers of the comps whose hbox has the given marker
+    pub fn with_marker_layers(mut self, marker: HBoxMarker, layers: PhysicsLayers) -> Self {
+        for comp in self
+            .comps
+            .iter_mut()
+            .filter(|comp| comp.hbox.get_marker() == marker)
+        {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Swaps out all the hboxes. Comps keep their layers if an old comp had the same marker.
+    /// NOTE: Doesn't re-hash, go through `TriggerTxHBoxesGeneric` for that.
+    pub(crate) fn set_hboxes<I: IntoIterator<Item = (TriggerTxKind, HBox)>>(&mut self, data: I) {
+        let old_comps = std::mem::take(&mut self.comps);
+        self.comps = data
+            .into_iter()
+            .map(|(kind, hbox)| TriggerTxComp {
+                kind,
+                layers: old_comps
+                    .iter()
+                    .find(|old| old.hbox.get_marker() == hbox.get_marker())
+                    .map(|old| old.layers)
+                    .unwrap_or_default(),
+                hbox,
+            })
+            .collect();
+    }
+    /// Tweaks every hbox in place, keeping kinds and layers.
+    /// NOTE: Doesn't re-hash, go through `TriggerTxHBoxesGeneric` for that.
+    pub(crate) fn update_hboxes<F: FnMut(&mut HBox)>(&mut self, mut f: F) {
+        for comp in &mut self.comps {
+            f(&mut comp.hbox);
+        }
+    }
     pub fn get_thboxes(&self, pos: Pos) -> Vec<HBox> {
         self.comps
             .iter()
@@ -90,3 +192,45 @@ impl<TriggerTxKind: TriggerKindTrait> TriggerTxGeneric<TriggerTxKind> {
             .collect()
     }
 }
+
+/// The way to change the hboxes of a `TriggerTxGeneric`.
+/// The spatial hash is updated right away, so queries and moves later in the same frame see the
+/// new shape.
+#[derive(SystemParam)]
+pub struct TriggerTxHBoxesGeneric<'w, 's, TriggerTxKind: TriggerKindTrait> {
+    ttx_q: Query<
+        'w,
+        's,
+        (
+            &'static Pos,
+            &'static mut TriggerTxGeneric<TriggerTxKind>,
+            &'static mut SpatKeys<SpatHashTriggerTx>,
+        ),
+    >,
+    spat_hash: ResMut<'w, SpatHash<SpatHashTriggerTx>>,
+}
+impl<TriggerTxKind: TriggerKindTrait> TriggerTxHBoxesGeneric<'_, '_, TriggerTxKind> {
+    /// Swaps out all the hboxes. Comps keep their layers if an old comp had the same marker.
+    /// Does nothing if `eid` has no (hashed) `TriggerTxGeneric`.
+    pub fn set<I: IntoIterator<Item = (TriggerTxKind, HBox)>>(&mut self, eid: Entity, data: I) {
+        self.edit(eid, |ttx| ttx.set_hboxes(data));
+    }
+    /// Tweaks every hbox in place, keeping kinds and layers.
+    /// Does nothing if `eid` has no (hashed) `TriggerTxGeneric`.
+    pub fn update<F: FnMut(&mut HBox)>(&mut self, eid: Entity, f: F) {
+        self.edit(eid, |ttx| ttx.update_hboxes(f));
+    }
+    /// Runs `f` on the tx and re-hashes it. Does nothing if `eid` has no (hashed) tx.
+    pub(crate) fn edit<F: FnOnce(&mut TriggerTxGeneric<TriggerTxKind>)>(
+        &mut self,
+        eid: Entity,
+        f: F,
+    ) {
+        let Ok((pos, mut ttx, mut spat_keys)) = self.ttx_q.get_mut(eid) else {
+            return;
+        };
+        f(&mut ttx);
+        let hboxes = ttx.comps.iter().map(|c| c.hbox.clone()).collect();
+        *spat_keys = self.spat_hash.update(eid, &spat_keys, *pos, hboxes);
+    }
+}
@@ -74,10 +74,12 @@ impl<LdtkRoot: LdtkRootKind, TriggerRxKind: TriggerKindTrait, TriggerTxKind: Tri
         app.add_plugins((
             CompositionPlugin::new(self.composition_settings.clone()),
             AnimPlugin::new(self.anim_settings.clone()),
-            BulletTimePlugin::default(),
+            BulletTimePlugin::new(self.physics_settings.is_fixed_timestep()),
             LdtkPlugin::<LdtkRoot>::default(),
             ParticlePlugin,
-            PhysicsPluginGeneric::<TriggerRxKind, TriggerTxKind>::default(),
+            PhysicsPluginGeneric::<TriggerRxKind, TriggerTxKind>::new(
+                self.physics_settings.clone(),
+            ),
             ShaderPlugin,
             InputPlugin,
         ));